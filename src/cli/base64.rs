@@ -1,16 +1,29 @@
 use crate::{
     get_string_from_path,
-    process::{decode_base64, encode_base64},
-    utils::verify_file,
+    process::{
+        decode_base64_stream, encode_base64_stream, encode_data_uri, parse_data_uri, sniff_mime,
+        Base64DecodeMode, Base64LineWrap,
+    },
+    utils::{get_reader_from_path, get_writer_from_path, verify_file},
     CmdExecutor,
 };
 use clap::{Parser, Subcommand};
-use std::{fmt::Display, str::FromStr};
+use std::{
+    fmt::Display,
+    io::{Read, Write},
+    str::FromStr,
+};
 
 #[derive(Subcommand)]
 pub enum Base64SubCommand {
     Encode(Base64EncodeOptions),
     Decode(Base64DecodeOptions),
+    #[command(
+        name = "datauri",
+        subcommand,
+        about = "生成或解析 data:<mime>;base64,... 形式的data URI"
+    )]
+    DataUri(DataUriSubCommand),
 }
 
 impl CmdExecutor for Base64SubCommand {
@@ -18,27 +31,110 @@ impl CmdExecutor for Base64SubCommand {
         match self {
             Base64SubCommand::Encode(opts) => opts.execute().await,
             Base64SubCommand::Decode(opts) => opts.execute().await,
+            Base64SubCommand::DataUri(sub_cmd) => sub_cmd.execute().await,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum DataUriSubCommand {
+    #[command(about = "将文件转换为data URI")]
+    Encode(DataUriEncodeOptions),
+    #[command(about = "解析data URI，输出MIME类型及解码后的内容")]
+    Decode(DataUriDecodeOptions),
+}
+
+impl CmdExecutor for DataUriSubCommand {
+    async fn execute(&self) -> anyhow::Result<()> {
+        match self {
+            DataUriSubCommand::Encode(opts) => opts.execute().await,
+            DataUriSubCommand::Decode(opts) => opts.execute().await,
         }
     }
 }
 
+#[derive(Debug, Parser)]
+pub struct DataUriEncodeOptions {
+    /// 需要转换的文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file, default_value = "-")]
+    input: String,
+
+    /// MIME类型，默认根据文件开头的特征字节或扩展名识别
+    #[arg(short, long)]
+    mime: Option<String>,
+}
+
+impl CmdExecutor for DataUriEncodeOptions {
+    async fn execute(&self) -> anyhow::Result<()> {
+        let mut data = Vec::new();
+        get_reader_from_path(&self.input)?.read_to_end(&mut data)?;
+        let mime = match &self.mime {
+            Some(mime) => mime.as_str(),
+            None => sniff_mime(&data, &self.input),
+        };
+        println!("{}", encode_data_uri(&data, mime));
+        Ok(())
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct DataUriDecodeOptions {
+    /// data URI所在的文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file, default_value = "-")]
+    input: String,
+
+    /// 解码内容的输出文件路径,“-”为输出到标准输出，二进制内容应输出到文件
+    #[arg(short, long, default_value = "-")]
+    output: String,
+}
+
+impl CmdExecutor for DataUriDecodeOptions {
+    async fn execute(&self) -> anyhow::Result<()> {
+        let uri = get_string_from_path(&self.input)?;
+        let data_uri = parse_data_uri(&uri)?;
+        // MIME类型输出到标准错误，避免与输出到标准输出的内容混在一起
+        eprintln!("MIME类型: {}", data_uri.mime);
+        let mut writer = get_writer_from_path(&self.output)?;
+        writer.write_all(&data_uri.data)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
 #[derive(Debug, Parser)]
 pub struct Base64EncodeOptions {
-    /// 需要解析的base64文件路径,“-”为从标准输入读取
+    /// 需要编码的文件路径，可以是任意二进制文件,“-”为从标准输入读取
     #[arg(short, long, value_parser=verify_file, default_value = "-")]
     input: String,
 
-    /// 解析的base64模式
+    /// 解析的base64模式，可选 urlsafe、urlsafe-pad、standard、standard-nopad、bcrypt、crypt
     #[arg(short, long, value_parser=verify_base64_format, default_value = "urlsafe")]
     format: Base64FormatType,
+
+    /// 每行的最大字符数，0为不折行
+    #[arg(short, long, default_value_t = 0, conflicts_with = "mime")]
+    wrap: usize,
+
+    /// 按MIME格式每76个字符使用CRLF折行
+    #[arg(long, default_value_t = false)]
+    mime: bool,
 }
 
 impl CmdExecutor for Base64EncodeOptions {
     async fn execute(&self) -> anyhow::Result<()> {
-        let content = get_string_from_path(&self.input)?;
-        let encode_base64 = encode_base64(content.as_str(), self.format)?;
-
-        println!("{}", encode_base64);
+        let wrap = if self.mime {
+            Some(Base64LineWrap::MIME)
+        } else {
+            (self.wrap > 0).then_some(Base64LineWrap {
+                width: self.wrap,
+                crlf: false,
+            })
+        };
+        let mut reader = get_reader_from_path(&self.input)?;
+        let mut writer = get_writer_from_path("-")?;
+        encode_base64_stream(&mut reader, &mut writer, self.format, wrap)?;
+        writeln!(writer)?;
+        writer.flush()?;
         Ok(())
     }
 }
@@ -49,32 +145,62 @@ pub struct Base64DecodeOptions {
     #[arg(short, long, value_parser=verify_file, default_value = "-")]
     input: String,
 
-    /// 解析的base64模式
+    /// 解析的base64模式，可选 urlsafe、urlsafe-pad、standard、standard-nopad、bcrypt、crypt
     #[arg(short, long, value_parser=verify_base64_format, default_value = "urlsafe")]
     format: Base64FormatType,
+
+    /// 宽松模式，忽略所有空白字符，允许缺少或多余的填充
+    #[arg(long, default_value_t = false)]
+    lenient: bool,
+
+    /// 自动识别标准或URL安全字母表及填充，忽略 --format
+    #[arg(long, default_value_t = false)]
+    auto: bool,
+
+    /// 解码结果的输出文件路径,“-”为输出到标准输出，二进制内容应输出到文件
+    #[arg(short, long, default_value = "-")]
+    output: String,
 }
 
 impl CmdExecutor for Base64DecodeOptions {
     async fn execute(&self) -> anyhow::Result<()> {
-        let content = get_string_from_path(&self.input)?;
-        let decode_base64 = decode_base64(content.as_str(), self.format)?;
-
-        println!("{}", decode_base64);
+        let mut reader = get_reader_from_path(&self.input)?;
+        let mut writer = get_writer_from_path(&self.output)?;
+        let mode = if self.auto {
+            Base64DecodeMode::Auto
+        } else if self.lenient {
+            Base64DecodeMode::Lenient
+        } else {
+            Base64DecodeMode::Strict
+        };
+        decode_base64_stream(&mut reader, &mut writer, self.format, mode)?;
+        writer.flush()?;
         Ok(())
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Base64FormatType {
+    // URL安全字母表，不填充
     UrlSafe,
+    UrlSafePad,
+    // 标准字母表，填充
     Standard,
+    StandardNoPad,
+    // bcrypt及crypt(3)使用的字母表，不填充
+    Bcrypt,
+    Crypt,
 }
 
 impl From<Base64FormatType> for &'static str {
     fn from(value: Base64FormatType) -> Self {
         match value {
             Base64FormatType::UrlSafe => "urlsafe",
+            Base64FormatType::UrlSafePad => "urlsafe-pad",
             Base64FormatType::Standard => "standard",
+            Base64FormatType::StandardNoPad => "standard-nopad",
+            Base64FormatType::Bcrypt => "bcrypt",
+            Base64FormatType::Crypt => "crypt",
         }
     }
 }
@@ -85,7 +211,11 @@ impl FromStr for Base64FormatType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "urlsafe" => Ok(Base64FormatType::UrlSafe),
+            "urlsafe-pad" => Ok(Base64FormatType::UrlSafePad),
             "standard" => Ok(Base64FormatType::Standard),
+            "standard-nopad" => Ok(Base64FormatType::StandardNoPad),
+            "bcrypt" => Ok(Base64FormatType::Bcrypt),
+            "crypt" => Ok(Base64FormatType::Crypt),
             _ => Err(format!("Invalid base64 format type: {}", s)),
         }
     }
@@ -0,0 +1,105 @@
+use crate::{decode_in_file, encode_in_file, utils::verify_file, Base64FormatType, CmdExecutor};
+use anyhow::Result;
+use clap::Parser;
+use std::{fmt::Display, str::FromStr};
+
+#[derive(Debug, Parser)]
+pub struct CodecOptions {
+    /// 编码方式，可选 hex、base32、base32-crockford、base58、base58check、ascii85、z85、percent、
+    /// base64、base64url 及 base64-<模式>（模式同 base64 命令的 --format）
+    #[arg(value_parser=parse_codec_type)]
+    pub codec: CodecType,
+
+    /// 输入文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file, default_value = "-")]
+    pub input: String,
+
+    /// 输出文件路径,“-”为输出到标准输出，解码二进制内容时应输出到文件
+    #[arg(short, long, default_value = "-")]
+    pub output: String,
+}
+
+// encode 和 decode 命令共用选项，分别实现执行逻辑
+#[derive(Debug, Parser)]
+pub struct EncodeOptions {
+    #[command(flatten)]
+    pub codec: CodecOptions,
+}
+
+#[derive(Debug, Parser)]
+pub struct DecodeOptions {
+    #[command(flatten)]
+    pub codec: CodecOptions,
+}
+
+impl CmdExecutor for EncodeOptions {
+    async fn execute(&self) -> Result<()> {
+        let opts = &self.codec;
+        encode_in_file(&opts.input, &opts.output, opts.codec)
+    }
+}
+
+impl CmdExecutor for DecodeOptions {
+    async fn execute(&self) -> Result<()> {
+        let opts = &self.codec;
+        decode_in_file(&opts.input, &opts.output, opts.codec)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecType {
+    Hex,
+    Base32,
+    Base32Crockford,
+    Base58,
+    Base58Check,
+    Base64(Base64FormatType),
+    Ascii85,
+    Z85,
+    Percent,
+}
+
+impl FromStr for CodecType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(CodecType::Hex),
+            "base32" => Ok(CodecType::Base32),
+            "base32-crockford" | "crockford" => Ok(CodecType::Base32Crockford),
+            "base58" => Ok(CodecType::Base58),
+            "base58check" => Ok(CodecType::Base58Check),
+            "base64" => Ok(CodecType::Base64(Base64FormatType::Standard)),
+            "base64url" => Ok(CodecType::Base64(Base64FormatType::UrlSafe)),
+            "ascii85" | "base85" => Ok(CodecType::Ascii85),
+            "z85" => Ok(CodecType::Z85),
+            "percent" | "url" => Ok(CodecType::Percent),
+            s => match s.strip_prefix("base64-") {
+                Some(format) => Ok(CodecType::Base64(format.parse()?)),
+                None => Err(format!("不支持的编码方式: {}", s)),
+            },
+        }
+    }
+}
+
+impl Display for CodecType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodecType::Hex => write!(f, "hex"),
+            CodecType::Base32 => write!(f, "base32"),
+            CodecType::Base32Crockford => write!(f, "base32-crockford"),
+            CodecType::Base58 => write!(f, "base58"),
+            CodecType::Base58Check => write!(f, "base58check"),
+            CodecType::Base64(format) => {
+                write!(f, "base64-{}", Into::<&'static str>::into(*format))
+            }
+            CodecType::Ascii85 => write!(f, "ascii85"),
+            CodecType::Z85 => write!(f, "z85"),
+            CodecType::Percent => write!(f, "percent"),
+        }
+    }
+}
+
+fn parse_codec_type(s: &str) -> Result<CodecType, String> {
+    s.parse()
+}
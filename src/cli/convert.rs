@@ -0,0 +1,183 @@
+use super::CmdExecutor;
+use crate::{
+    convert_in_file,
+    utils::{parse_delimiter, verify_file},
+    ConvertConfig, CsvEncoding,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::{fmt::Display, path::Path, str::FromStr};
+
+// 结构化数据格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertFormatType {
+    Json,
+    Yaml,
+    Toml,
+    Csv,
+}
+
+impl ConvertFormatType {
+    // 根据文件扩展名判断格式
+    pub fn from_path(path: &str) -> Option<Self> {
+        Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.to_lowercase().parse().ok())
+    }
+}
+
+impl From<ConvertFormatType> for &'static str {
+    fn from(value: ConvertFormatType) -> Self {
+        match value {
+            ConvertFormatType::Json => "json",
+            ConvertFormatType::Yaml => "yaml",
+            ConvertFormatType::Toml => "toml",
+            ConvertFormatType::Csv => "csv",
+        }
+    }
+}
+
+impl FromStr for ConvertFormatType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ConvertFormatType::Json),
+            "yaml" | "yml" => Ok(ConvertFormatType::Yaml),
+            "toml" => Ok(ConvertFormatType::Toml),
+            "csv" => Ok(ConvertFormatType::Csv),
+            _ => Err("不支持的文件格式"),
+        }
+    }
+}
+
+impl Display for ConvertFormatType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
+
+fn parse_convert_format_value(s: &str) -> Result<ConvertFormatType, &'static str> {
+    s.parse()
+}
+
+// csv字段的引号策略
+#[derive(Debug, Clone, Copy)]
+pub enum CsvQuoteStyle {
+    Necessary,
+    Always,
+    NonNumeric,
+    Never,
+}
+
+impl From<CsvQuoteStyle> for &'static str {
+    fn from(value: CsvQuoteStyle) -> Self {
+        match value {
+            CsvQuoteStyle::Necessary => "necessary",
+            CsvQuoteStyle::Always => "always",
+            CsvQuoteStyle::NonNumeric => "non-numeric",
+            CsvQuoteStyle::Never => "never",
+        }
+    }
+}
+
+impl From<CsvQuoteStyle> for csv::QuoteStyle {
+    fn from(value: CsvQuoteStyle) -> Self {
+        match value {
+            CsvQuoteStyle::Necessary => csv::QuoteStyle::Necessary,
+            CsvQuoteStyle::Always => csv::QuoteStyle::Always,
+            CsvQuoteStyle::NonNumeric => csv::QuoteStyle::NonNumeric,
+            CsvQuoteStyle::Never => csv::QuoteStyle::Never,
+        }
+    }
+}
+
+impl FromStr for CsvQuoteStyle {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "necessary" => Ok(CsvQuoteStyle::Necessary),
+            "always" => Ok(CsvQuoteStyle::Always),
+            "non-numeric" => Ok(CsvQuoteStyle::NonNumeric),
+            "never" => Ok(CsvQuoteStyle::Never),
+            _ => Err("不支持的引号策略"),
+        }
+    }
+}
+
+impl Display for CsvQuoteStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
+
+fn parse_quote_style(s: &str) -> Result<CsvQuoteStyle, &'static str> {
+    s.parse()
+}
+
+#[derive(Debug, Parser)]
+pub struct ConvertOptions {
+    /// 需要转换的文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file, default_value = "-")]
+    pub input: String,
+
+    /// 输出文件路径，默认当前目录output.{format}，“-”为输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// 输入文件格式，默认根据文件扩展名或内容判断
+    #[arg(long, value_parser=parse_convert_format_value)]
+    pub from: Option<ConvertFormatType>,
+
+    /// 输出文件格式，可选 json、yaml、toml、csv，默认根据输出文件扩展名判断，否则为csv
+    #[arg(short, long, value_parser=parse_convert_format_value)]
+    pub format: Option<ConvertFormatType>,
+
+    /// csv分隔符，默认逗号，可用 \t 表示制表符
+    #[arg(short, long, value_parser=parse_delimiter, default_value = ",")]
+    pub delimiter: u8,
+
+    /// csv引号策略，可选 necessary、always、non-numeric、never
+    #[arg(long, value_parser=parse_quote_style, default_value = "necessary")]
+    pub quote_style: CsvQuoteStyle,
+
+    /// 输入文件的编码，可选 utf-8、utf-8-bom、utf-16le、utf-16be、gbk、gb18030，默认根据BOM及内容自动识别
+    #[arg(long)]
+    pub encoding: Option<CsvEncoding>,
+
+    /// 输出文件的编码，可选 utf-8、utf-8-bom、utf-16le、utf-16be、gbk、gb18030
+    #[arg(long, default_value = "utf-8")]
+    pub output_encoding: CsvEncoding,
+}
+
+impl CmdExecutor for ConvertOptions {
+    async fn execute(&self) -> Result<()> {
+        let from = self
+            .from
+            .or_else(|| ConvertFormatType::from_path(&self.input));
+        let format = self
+            .format
+            .or_else(|| {
+                self.output
+                    .as_deref()
+                    .and_then(ConvertFormatType::from_path)
+            })
+            .unwrap_or(ConvertFormatType::Csv);
+        let output = if let Some(output) = &self.output {
+            output.clone()
+        } else {
+            format!("output.{}", format)
+        };
+        let config = ConvertConfig {
+            delimiter: self.delimiter,
+            quote_style: self.quote_style,
+            encoding: self.encoding,
+            output_encoding: self.output_encoding,
+        };
+        convert_in_file(&self.input, output, from, format, &config)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use super::CsvDialectArgs;
+use crate::{diff_csv_in_file, utils::verify_file, CmdExecutor, CsvConvertConfig};
+use anyhow::Result;
+use clap::Parser;
+use std::{
+    fmt::Display,
+    io::{stdout, IsTerminal},
+    str::FromStr,
+};
+
+// csv差异的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvDiffFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl From<CsvDiffFormat> for &'static str {
+    fn from(value: CsvDiffFormat) -> Self {
+        match value {
+            CsvDiffFormat::Text => "text",
+            CsvDiffFormat::Json => "json",
+            CsvDiffFormat::Csv => "csv",
+        }
+    }
+}
+
+impl FromStr for CsvDiffFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(CsvDiffFormat::Text),
+            "json" => Ok(CsvDiffFormat::Json),
+            "csv" => Ok(CsvDiffFormat::Csv),
+            _ => Err(format!("不支持的输出格式: {}", s)),
+        }
+    }
+}
+
+impl Display for CsvDiffFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct CsvDiffOptions {
+    /// 旧的csv文件路径
+    #[arg(value_parser=verify_file)]
+    pub old: String,
+
+    /// 新的csv文件路径
+    #[arg(value_parser=verify_file)]
+    pub new: String,
+
+    /// 用于匹配行的键列，如 id 或 name,birthday
+    #[arg(short, long, value_delimiter = ',', required = true)]
+    pub key: Vec<String>,
+
+    /// 输出格式，可选 text(终端文本)、json(JSON Patch风格)、csv(带变化类型列)
+    #[arg(short, long, default_value = "text")]
+    pub format: CsvDiffFormat,
+
+    /// 输出文件路径,“-”为输出到标准输出
+    #[arg(short, long, default_value = "-")]
+    pub output: String,
+
+    /// 不使用颜色输出，默认输出到终端时使用颜色
+    #[arg(long, default_value_t = false)]
+    pub no_color: bool,
+
+    #[command(flatten)]
+    pub dialect: CsvDialectArgs,
+}
+
+impl CmdExecutor for CsvDiffOptions {
+    async fn execute(&self) -> Result<()> {
+        let config = CsvConvertConfig {
+            dialect: (&self.dialect).into(),
+            ..Default::default()
+        };
+        let color = !self.no_color && self.output == "-" && stdout().is_terminal();
+        diff_csv_in_file(
+            &self.old,
+            &self.new,
+            &self.output,
+            &self.key,
+            self.format,
+            &config,
+            color,
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use super::CsvDialectArgs;
+use crate::{
+    mask_csv_in_file, utils::verify_file, CmdExecutor, CsvEncoding, CsvFakeKind, CsvMaskConfig,
+    CsvMaskKey, CsvMaskRule, CsvMaskStrategy,
+};
+use anyhow::Result;
+use clap::{ArgGroup, Parser};
+
+#[derive(Debug, Parser)]
+#[command(group(ArgGroup::new("secret").required(true).args(["seed", "key"])))]
+pub struct CsvMaskOptions {
+    /// 输入文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file, default_value = "-")]
+    pub input: String,
+
+    /// 输出文件路径,“-”为输出到标准输出
+    #[arg(short, long, default_value = "-")]
+    pub output: String,
+
+    /// 脱敏规则，格式为 列名=方式，可指定多个。方式可选 redact[:替换内容]、hash、
+    /// fake:name|email|phone、date[:最大偏移天数]、noise[:最大百分比]
+    #[arg(short, long = "rule", value_parser=parse_mask_rule, required = true)]
+    pub rules: Vec<CsvMaskRule>,
+
+    /// 生成密钥的种子，相同种子对相同的值生成相同的结果，可用于多个文件的关联
+    #[arg(long)]
+    pub seed: Option<String>,
+
+    /// 32字节的blake3密钥文件，可使用 text generate 生成
+    #[arg(long, value_parser=verify_file)]
+    pub key: Option<String>,
+
+    /// 输出文件的编码，可选 utf-8、utf-8-bom、utf-16le、utf-16be、gbk、gb18030
+    #[arg(long, default_value = "utf-8")]
+    pub output_encoding: CsvEncoding,
+
+    #[command(flatten)]
+    pub dialect: CsvDialectArgs,
+}
+
+// 日期偏移的最大天数
+const MAX_DATE_SHIFT_DAYS: i64 = 36_500;
+// 数值噪声的最大百分比
+const MAX_NOISE_PERCENT: f64 = 100.0;
+
+// 解析 列名=方式[:参数] 形式的脱敏规则
+fn parse_mask_rule(s: &str) -> Result<CsvMaskRule, String> {
+    let (column, strategy) = s
+        .split_once('=')
+        .ok_or_else(|| format!("脱敏规则格式错误，应为 列名=方式: {}", s))?;
+    let (name, arg) = match strategy.split_once(':') {
+        Some((name, arg)) => (name.trim(), Some(arg)),
+        None => (strategy.trim(), None),
+    };
+    let strategy = match (name, arg) {
+        ("redact", arg) => CsvMaskStrategy::Redact(arg.unwrap_or("***").to_string()),
+        ("hash", None) => CsvMaskStrategy::Hash,
+        ("fake", Some("name")) => CsvMaskStrategy::Fake(CsvFakeKind::Name),
+        ("fake", Some("email")) => CsvMaskStrategy::Fake(CsvFakeKind::Email),
+        ("fake", Some("phone")) => CsvMaskStrategy::Fake(CsvFakeKind::Phone),
+        ("date", arg) => {
+            let days = arg
+                .map_or(Ok(30), str::parse)
+                .map_err(|_| format!("日期偏移天数应为整数: {}", s))?;
+            if days < 0 {
+                return Err(format!("日期偏移天数不能为负数: {}", s));
+            }
+            if days > MAX_DATE_SHIFT_DAYS {
+                return Err(format!(
+                    "日期偏移天数不能大于{}: {}",
+                    MAX_DATE_SHIFT_DAYS, s
+                ));
+            }
+            CsvMaskStrategy::DateShift(days)
+        }
+        ("noise", arg) => {
+            let percent: f64 = arg
+                .map_or(Ok(5.0), str::parse)
+                .map_err(|_| format!("噪声百分比应为数值: {}", s))?;
+            if !percent.is_finite() || percent < 0.0 {
+                return Err(format!("噪声百分比应为非负数值: {}", s));
+            }
+            if percent > MAX_NOISE_PERCENT {
+                return Err(format!("噪声百分比不能大于{}: {}", MAX_NOISE_PERCENT, s));
+            }
+            CsvMaskStrategy::Noise(percent)
+        }
+        _ => return Err(format!("不支持的脱敏方式: {}", strategy)),
+    };
+    if column.trim().is_empty() {
+        return Err(format!("脱敏规则缺少列名: {}", s));
+    }
+    Ok(CsvMaskRule {
+        column: column.trim().to_string(),
+        strategy,
+    })
+}
+
+impl CmdExecutor for CsvMaskOptions {
+    async fn execute(&self) -> Result<()> {
+        let key = match (&self.seed, &self.key) {
+            (_, Some(path)) => CsvMaskKey::File(path.clone()),
+            (Some(seed), None) => CsvMaskKey::Seed(seed.clone()),
+            (None, None) => unreachable!("clap要求指定 --seed 或 --key"),
+        };
+        let config = CsvMaskConfig {
+            dialect: self.dialect.with_output_encoding(self.output_encoding),
+            rules: self.rules.clone(),
+            key,
+        };
+        let count = mask_csv_in_file(&self.input, &self.output, &config)?;
+        eprintln!("脱敏完成，共{}行", count);
+        Ok(())
+    }
+}
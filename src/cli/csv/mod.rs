@@ -0,0 +1,542 @@
+mod diff;
+mod mask;
+mod plumbing;
+mod query;
+mod schema;
+mod stats;
+mod view;
+
+pub use self::{diff::CsvDiffFormat, plumbing::CsvJoinKind};
+
+use self::{
+    diff::CsvDiffOptions,
+    mask::CsvMaskOptions,
+    plumbing::{CsvCatOptions, CsvDedupOptions, CsvJoinOptions, CsvSplitOptions},
+    query::CsvQueryOptions,
+    schema::{CsvSchemaOptions, CsvValidateOptions},
+    stats::CsvStatsOptions,
+    view::CsvViewOptions,
+};
+use super::CmdExecutor;
+use crate::{
+    convert_csv_batch, convert_csv_batch_files, convert_csv_in_file, glob_files, is_glob_pattern,
+    utils::{parse_ascii_char, parse_delimiter, verify_input},
+    watch_csv_inputs, CsvBatchConfig, CsvBatchReport, CsvConvertConfig, CsvDialect, CsvSortKey,
+};
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use clap::{Args, Parser, Subcommand};
+use std::{
+    fmt::Display,
+    io::{stdout, IsTerminal},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum CsvFormatType {
+    Json,
+    Yaml,
+    Toml,
+    Ndjson,
+    Xml,
+    Markdown,
+    Html,
+    MessagePack,
+    Cbor,
+}
+
+// 实现 From<CsvFormatType> for &'static str
+// From和Into用于值到值的转换，From是Into的逆运算。
+// 优先考虑实现From，因为当From<T>实现后，由于标准库的泛型实现，Into<U>也会自动得到实现。
+// From不应失败，设计用于无损转换；如果可能失败或不是无损转换，应使用TryFrom。
+// 这里通过实现From，可以使用.into()方法直接将CsvFormatType枚举转换为静态字符串引用。
+impl From<CsvFormatType> for &'static str {
+    fn from(value: CsvFormatType) -> Self {
+        match value {
+            CsvFormatType::Json => "json",
+            CsvFormatType::Yaml => "yaml",
+            CsvFormatType::Toml => "toml",
+            CsvFormatType::Ndjson => "ndjson",
+            CsvFormatType::Xml => "xml",
+            CsvFormatType::Markdown => "md",
+            CsvFormatType::Html => "html",
+            CsvFormatType::MessagePack => "msgpack",
+            CsvFormatType::Cbor => "cbor",
+        }
+    }
+}
+
+// 实现 FromStr
+// FromStr用于字符串到值的转换。
+impl FromStr for CsvFormatType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(CsvFormatType::Json),
+            "yaml" => Ok(CsvFormatType::Yaml),
+            "toml" => Ok(CsvFormatType::Toml),
+            "ndjson" | "jsonl" => Ok(CsvFormatType::Ndjson),
+            "xml" => Ok(CsvFormatType::Xml),
+            "md" | "markdown" => Ok(CsvFormatType::Markdown),
+            "html" => Ok(CsvFormatType::Html),
+            "msgpack" | "messagepack" => Ok(CsvFormatType::MessagePack),
+            "cbor" => Ok(CsvFormatType::Cbor),
+            _ => Err("不支持的文件格式"),
+        }
+    }
+}
+
+impl Display for CsvFormatType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
+
+fn parse_csv_format_value(s: &str) -> Result<CsvFormatType, &'static str> {
+    s.parse()
+}
+
+// csv列的目标类型，用于 --type 指定列类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvColumnType {
+    String,
+    Int,
+    Float,
+    Bool,
+    Date,
+}
+
+impl From<CsvColumnType> for &'static str {
+    fn from(value: CsvColumnType) -> Self {
+        match value {
+            CsvColumnType::String => "string",
+            CsvColumnType::Int => "int",
+            CsvColumnType::Float => "float",
+            CsvColumnType::Bool => "bool",
+            CsvColumnType::Date => "date",
+        }
+    }
+}
+
+impl FromStr for CsvColumnType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "string" | "str" => Ok(CsvColumnType::String),
+            "int" | "integer" => Ok(CsvColumnType::Int),
+            "float" | "number" => Ok(CsvColumnType::Float),
+            "bool" | "boolean" => Ok(CsvColumnType::Bool),
+            "date" => Ok(CsvColumnType::Date),
+            _ => Err(format!("不支持的列类型: {}", s)),
+        }
+    }
+}
+
+impl Display for CsvColumnType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
+
+// csv文件的字符编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvEncoding {
+    Utf8,
+    // 带BOM的UTF-8，便于Excel识别
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Gbk,
+    Gb18030,
+}
+
+impl From<CsvEncoding> for &'static str {
+    fn from(value: CsvEncoding) -> Self {
+        match value {
+            CsvEncoding::Utf8 => "utf-8",
+            CsvEncoding::Utf8Bom => "utf-8-bom",
+            CsvEncoding::Utf16Le => "utf-16le",
+            CsvEncoding::Utf16Be => "utf-16be",
+            CsvEncoding::Gbk => "gbk",
+            CsvEncoding::Gb18030 => "gb18030",
+        }
+    }
+}
+
+impl FromStr for CsvEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(CsvEncoding::Utf8),
+            "utf-8-bom" | "utf-8-sig" | "utf8bom" => Ok(CsvEncoding::Utf8Bom),
+            "utf-16le" | "utf-16" | "utf16" => Ok(CsvEncoding::Utf16Le),
+            "utf-16be" => Ok(CsvEncoding::Utf16Be),
+            "gbk" | "cp936" => Ok(CsvEncoding::Gbk),
+            "gb18030" => Ok(CsvEncoding::Gb18030),
+            _ => Err(format!("不支持的编码: {}", s)),
+        }
+    }
+}
+
+impl Display for CsvEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
+
+// 解析 列名=类型 形式的参数，如 age=int
+fn parse_column_type(s: &str) -> Result<(String, CsvColumnType), String> {
+    let (column, column_type) = s
+        .split_once('=')
+        .ok_or_else(|| format!("列类型格式错误，应为 列名=类型: {}", s))?;
+    Ok((column.trim().to_string(), column_type.trim().parse()?))
+}
+
+// 解析 原列名=新列名 形式的参数
+fn parse_rename(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((from, to)) if !from.trim().is_empty() && !to.trim().is_empty() => {
+            Ok((from.trim().to_string(), to.trim().to_string()))
+        }
+        _ => Err(format!("重命名格式错误，应为 原列名=新列名: {}", s)),
+    }
+}
+
+// 解析 列名[:asc|desc] 形式的排序参数
+fn parse_sort_key(s: &str) -> Result<CsvSortKey, String> {
+    let (column, descending) = match s.rsplit_once(':') {
+        Some((column, "desc")) => (column, true),
+        Some((column, "asc")) => (column, false),
+        _ => (s, false),
+    };
+    if column.trim().is_empty() {
+        return Err(format!("排序字段格式错误: {}", s));
+    }
+    Ok(CsvSortKey {
+        column: column.trim().to_string(),
+        descending,
+    })
+}
+
+// csv方言参数，各子命令共用
+#[derive(Debug, Args)]
+pub struct CsvDialectArgs {
+    /// csv分隔符，可用 \t 表示制表符，默认根据文件内容自动识别
+    #[arg(short, long, value_parser=parse_delimiter)]
+    pub delimiter: Option<u8>,
+
+    /// 引号字符
+    #[arg(long, value_parser=parse_ascii_char, default_value = "\"")]
+    pub quote: u8,
+
+    /// 转义字符，如 \，默认只识别双写引号
+    #[arg(long, value_parser=parse_ascii_char)]
+    pub escape: Option<u8>,
+
+    /// 文件不包含表头，列名自动生成为 col1..colN
+    #[arg(long, default_value_t = false)]
+    pub no_header: bool,
+
+    /// 注释行的起始字符，如 #
+    #[arg(long, value_parser=parse_ascii_char)]
+    pub comment: Option<u8>,
+
+    /// 允许每行的字段数不一致
+    #[arg(long, default_value_t = false)]
+    pub flexible: bool,
+
+    /// 去除字段首尾的空白
+    #[arg(long, default_value_t = false)]
+    pub trim: bool,
+
+    /// 输入文件的编码，可选 utf-8、utf-8-bom、utf-16le、utf-16be、gbk、gb18030，默认根据BOM及内容自动识别
+    #[arg(long)]
+    pub encoding: Option<CsvEncoding>,
+}
+
+impl From<&CsvDialectArgs> for CsvDialect {
+    fn from(args: &CsvDialectArgs) -> Self {
+        CsvDialect {
+            delimiter: args.delimiter,
+            quote: args.quote,
+            escape: args.escape,
+            has_headers: !args.no_header,
+            comment: args.comment,
+            flexible: args.flexible,
+            trim: args.trim,
+            encoding: args.encoding,
+            output_encoding: CsvEncoding::Utf8,
+        }
+    }
+}
+
+impl CsvDialectArgs {
+    // 输出csv或文本的命令另外指定输出的编码
+    pub fn with_output_encoding(&self, output_encoding: CsvEncoding) -> CsvDialect {
+        CsvDialect {
+            output_encoding,
+            ..self.into()
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CsvSubCommand {
+    #[command(about = "使用SQL查询一个或多个csv文件")]
+    Query(CsvQueryOptions),
+    #[command(about = "统计csv每一列的类型、空值、取值范围及分布")]
+    Stats(CsvStatsOptions),
+    #[command(about = "根据csv内容推断JSON Schema")]
+    Schema(CsvSchemaOptions),
+    #[command(about = "使用JSON Schema校验csv的每一行")]
+    Validate(CsvValidateOptions),
+    #[command(about = "按键列比较两个csv文件的新增、删除和修改")]
+    Diff(CsvDiffOptions),
+    #[command(about = "按键列连接两个csv文件，支持 inner、left、full")]
+    Join(CsvJoinOptions),
+    #[command(about = "合并多个csv文件，输出所有文件表头的并集")]
+    Cat(CsvCatOptions),
+    #[command(about = "按行数或列值将csv拆分为多个文件")]
+    Split(CsvSplitOptions),
+    #[command(about = "按整行或键列去除csv中重复的行")]
+    Dedup(CsvDedupOptions),
+    #[command(about = "以终端表格查看csv或json对象数组，支持分页、截断及限制行列")]
+    View(CsvViewOptions),
+    #[command(about = "按列脱敏csv：替换、哈希、生成假数据、日期偏移及数值噪声")]
+    Mask(CsvMaskOptions),
+}
+
+impl CmdExecutor for CsvSubCommand {
+    async fn execute(&self) -> Result<()> {
+        match self {
+            CsvSubCommand::Query(opts) => opts.execute().await,
+            CsvSubCommand::Stats(opts) => opts.execute().await,
+            CsvSubCommand::Schema(opts) => opts.execute().await,
+            CsvSubCommand::Validate(opts) => opts.execute().await,
+            CsvSubCommand::Diff(opts) => opts.execute().await,
+            CsvSubCommand::Join(opts) => opts.execute().await,
+            CsvSubCommand::Cat(opts) => opts.execute().await,
+            CsvSubCommand::Split(opts) => opts.execute().await,
+            CsvSubCommand::Dedup(opts) => opts.execute().await,
+            CsvSubCommand::View(opts) => opts.execute().await,
+            CsvSubCommand::Mask(opts) => opts.execute().await,
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct CsvOptions {
+    #[command(subcommand)]
+    pub command: Option<CsvSubCommand>,
+
+    /// 需要解析的csv文件路径,“-”为从标准输入读取，也可以是目录或glob模式如 'exports/**/*.csv'，此时批量转换到 --out-dir
+    #[arg(short, long, value_parser=verify_input, default_value = "-")]
+    pub input: String,
+
+    /// 输出文件路径,“-”为输出到标准输出，默认当标准输出为管道时输出到标准输出，否则为当前目录output.{format}
+    #[arg(short, long, conflicts_with = "out_dir")]
+    pub output: Option<String>,
+
+    /// 批量转换的输出目录，输出文件以输入文件命名并保留匹配到的子目录结构，默认为当前目录
+    #[arg(long)]
+    pub out_dir: Option<PathBuf>,
+
+    /// 批量转换时遇到错误立即停止，不再转换剩余的文件
+    #[arg(long, default_value_t = false)]
+    pub fail_fast: bool,
+
+    /// 批量转换时并行转换的文件数，默认为CPU核数
+    #[arg(short, long, default_value_t = 0)]
+    pub jobs: usize,
+
+    /// 输出文件格式，可选 json、yaml、toml、ndjson、xml、md、html、msgpack、cbor，默认json
+    #[arg(short, long, value_parser=parse_csv_format_value, default_value = "json")]
+    pub format: CsvFormatType,
+
+    /// 推断字段类型（整数、浮点数、布尔值，空值转为null），默认开启
+    #[arg(long, overrides_with = "no_infer")]
+    pub infer_types: bool,
+
+    /// 不推断字段类型，所有字段按字符串输出
+    #[arg(long, overrides_with = "infer_types")]
+    pub no_infer: bool,
+
+    /// 指定列类型，如 age=int,active=bool，可选 string、int、float、bool、date
+    #[arg(long = "type", value_parser=parse_column_type, value_delimiter = ',')]
+    pub types: Vec<(String, CsvColumnType)>,
+
+    /// toml输出的根表名，默认为输入文件名
+    #[arg(long)]
+    pub toml_key: Option<String>,
+
+    /// toml输出时作为表名的列，如 Name 输出为 [players.Buffon]，默认每行输出为 [[表名]]
+    #[arg(long)]
+    pub toml_key_column: Option<String>,
+
+    #[command(flatten)]
+    pub dialect: CsvDialectArgs,
+
+    /// 根据列名还原嵌套结构，如 address.city 还原为对象，tags[0] 还原为数组
+    #[arg(long, default_value_t = false)]
+    pub unflatten: bool,
+
+    /// 还原嵌套结构时列名的分隔符
+    #[arg(long, default_value = ".")]
+    pub unflatten_separator: String,
+
+    /// 只输出指定的列，如 name,age
+    #[arg(long, value_delimiter = ',')]
+    pub select: Vec<String>,
+
+    /// 重命名列，如 old=new，可指定多个
+    #[arg(long, value_parser=parse_rename, value_delimiter = ',')]
+    pub rename: Vec<(String, String)>,
+
+    /// 过滤表达式，如 "age>30 && nationality==Italy"，支持 == != > >= < <= ~=(包含) && || ! 和括号，含空格的列名使用反引号
+    #[arg(long = "where")]
+    pub filter: Option<String>,
+
+    /// 排序字段，如 age:desc,name，排序时需要读取全部记录
+    #[arg(long, value_parser=parse_sort_key, value_delimiter = ',')]
+    pub sort_by: Vec<CsvSortKey>,
+
+    /// 输出文件的编码，可选 utf-8、utf-8-bom、utf-16le、utf-16be、gbk、gb18030，二进制格式不支持
+    #[arg(long, default_value = "utf-8")]
+    pub output_encoding: CsvEncoding,
+
+    /// 在标准错误输出转换进度
+    #[arg(long, default_value_t = false)]
+    pub progress: bool,
+
+    /// 监听输入文件（或glob模式匹配的文件），变化后自动重新转换，转换失败时不退出
+    #[arg(short, long, default_value_t = false)]
+    pub watch: bool,
+}
+
+impl CmdExecutor for CsvOptions {
+    async fn execute(&self) -> Result<()> {
+        if let Some(command) = &self.command {
+            return command.execute().await;
+        }
+
+        let config = CsvConvertConfig {
+            infer_types: !self.no_infer,
+            column_types: self.types.iter().cloned().collect(),
+            toml_key: self.toml_key.clone().unwrap_or_else(|| {
+                Path::new(&self.input)
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .filter(|stem| *stem != "-")
+                    .unwrap_or("data")
+                    .to_string()
+            }),
+            toml_key_column: self.toml_key_column.clone(),
+            progress: self.progress,
+            unflatten: self.unflatten.then(|| self.unflatten_separator.clone()),
+            select: self.select.clone(),
+            rename: self.rename.clone(),
+            filter: self.filter.clone(),
+            sort_by: self.sort_by.clone(),
+            dialect: self.dialect.with_output_encoding(self.output_encoding),
+        };
+
+        if Path::new(&self.input).is_dir() {
+            // 目录输入时转换其中（包括子目录）的所有csv文件
+            let pattern = format!("{}/**/*.csv", self.input.trim_end_matches('/'));
+            return self.execute_batch(&pattern, &config);
+        }
+        if is_glob_pattern(&self.input) || self.out_dir.is_some() {
+            return self.execute_batch(&self.input, &config);
+        }
+
+        let output = if let Some(output) = &self.output {
+            output.clone()
+        } else if !stdout().is_terminal() {
+            "-".to_string()
+        } else {
+            format!("output.{}", self.format)
+        };
+        if self.watch {
+            return self.execute_watch(&output, &config);
+        }
+        convert_csv_in_file(self.input.clone(), output, self.format, &config)?;
+
+        Ok(())
+    }
+}
+
+// 输出批量转换中每个文件的结果及汇总
+fn print_batch_report(report: &CsvBatchReport) {
+    for (input, output) in &report.succeeded {
+        eprintln!("成功: {} -> {}", input.display(), output.display());
+    }
+    for (input, error) in &report.failed {
+        eprintln!("失败: {}: {}", input.display(), error);
+    }
+    eprintln!(
+        "转换完成，成功{}个，失败{}个，跳过{}个",
+        report.succeeded.len(),
+        report.failed.len(),
+        report.skipped
+    );
+}
+
+impl CsvOptions {
+    // 批量转换并输出每个文件的结果及汇总，存在失败的文件时返回错误
+    fn execute_batch(&self, pattern: &str, config: &CsvConvertConfig) -> Result<()> {
+        if pattern == "-" {
+            return Err(anyhow!("批量转换不支持从标准输入读取"));
+        }
+        let batch = CsvBatchConfig {
+            out_dir: self.out_dir.clone().unwrap_or_else(|| PathBuf::from(".")),
+            fail_fast: self.fail_fast,
+            jobs: self.jobs,
+            toml_key_from_file: self.toml_key.is_none(),
+        };
+        if self.watch {
+            // 先转换全部文件，之后只转换新增或修改的文件，转换失败时不退出
+            let convert = |inputs: &[PathBuf]| match convert_csv_batch_files(
+                pattern,
+                inputs,
+                self.format,
+                config,
+                &batch,
+            ) {
+                Ok(report) => print_batch_report(&report),
+                Err(e) => eprintln!("转换失败: {:#}", e),
+            };
+            convert(&glob_files(pattern)?);
+            eprintln!("正在监听 {} 的变化，按 Ctrl+C 退出", pattern);
+            return watch_csv_inputs(pattern, convert);
+        }
+        let report = convert_csv_batch(pattern, self.format, config, &batch)?;
+        print_batch_report(&report);
+        if report.failed.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("{}个文件转换失败", report.failed.len()))
+        }
+    }
+
+    // 监听单个输入文件，变化后重新转换，转换失败时输出错误并继续监听
+    fn execute_watch(&self, output: &str, config: &CsvConvertConfig) -> Result<()> {
+        if self.input == "-" {
+            return Err(anyhow!("监听模式不支持从标准输入读取"));
+        }
+        let convert = || {
+            let time = Local::now().format("%H:%M:%S");
+            match convert_csv_in_file(self.input.clone(), output.to_string(), self.format, config) {
+                Ok(()) => eprintln!("[{}] 已转换 {} -> {}", time, self.input, output),
+                Err(e) => eprintln!("[{}] 转换失败: {:#}", time, e),
+            }
+        };
+        convert();
+        eprintln!("正在监听 {} 的变化，按 Ctrl+C 退出", self.input);
+        watch_csv_inputs(&self.input, |_| convert())
+    }
+}
@@ -0,0 +1,207 @@
+use super::CsvDialectArgs;
+use crate::{
+    cat_csv_in_file, dedup_csv_in_file, join_csv_in_file, split_csv_in_file, utils::verify_file,
+    CmdExecutor, CsvEncoding, CsvSplitBy,
+};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+
+// csv连接的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvJoinKind {
+    Inner,
+    Left,
+    Full,
+}
+
+impl From<CsvJoinKind> for &'static str {
+    fn from(value: CsvJoinKind) -> Self {
+        match value {
+            CsvJoinKind::Inner => "inner",
+            CsvJoinKind::Left => "left",
+            CsvJoinKind::Full => "full",
+        }
+    }
+}
+
+impl FromStr for CsvJoinKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "inner" => Ok(CsvJoinKind::Inner),
+            "left" => Ok(CsvJoinKind::Left),
+            "full" | "outer" => Ok(CsvJoinKind::Full),
+            _ => Err(format!("不支持的连接方式: {}", s)),
+        }
+    }
+}
+
+impl Display for CsvJoinKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct CsvJoinOptions {
+    /// 左侧csv文件路径，逐行读取
+    #[arg(value_parser=verify_file)]
+    pub left: String,
+
+    /// 右侧csv文件路径，读入内存建立索引，应使用较小的文件
+    #[arg(value_parser=verify_file)]
+    pub right: String,
+
+    /// 用于连接的键列，两个文件中的列名需相同，如 id 或 name,birthday
+    #[arg(short, long, value_delimiter = ',', required = true)]
+    pub key: Vec<String>,
+
+    /// 连接方式，可选 inner、left、full
+    #[arg(long, default_value = "inner")]
+    pub kind: CsvJoinKind,
+
+    /// 输出文件路径,“-”为输出到标准输出
+    #[arg(short, long, default_value = "-")]
+    pub output: String,
+
+    /// 输出文件的编码，可选 utf-8、utf-8-bom、utf-16le、utf-16be、gbk、gb18030
+    #[arg(long, default_value = "utf-8")]
+    pub output_encoding: CsvEncoding,
+
+    #[command(flatten)]
+    pub dialect: CsvDialectArgs,
+}
+
+impl CmdExecutor for CsvJoinOptions {
+    async fn execute(&self) -> Result<()> {
+        let count = join_csv_in_file(
+            &self.left,
+            &self.right,
+            &self.output,
+            &self.key,
+            self.kind,
+            &self.dialect.with_output_encoding(self.output_encoding),
+        )?;
+        eprintln!("连接完成，共{}行", count);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct CsvCatOptions {
+    /// 要合并的csv文件路径，输出的列为所有文件表头的并集
+    #[arg(value_parser=verify_file, required = true)]
+    pub inputs: Vec<String>,
+
+    /// 输出文件路径,“-”为输出到标准输出
+    #[arg(short, long, default_value = "-")]
+    pub output: String,
+
+    /// 输出文件的编码，可选 utf-8、utf-8-bom、utf-16le、utf-16be、gbk、gb18030
+    #[arg(long, default_value = "utf-8")]
+    pub output_encoding: CsvEncoding,
+
+    #[command(flatten)]
+    pub dialect: CsvDialectArgs,
+}
+
+impl CmdExecutor for CsvCatOptions {
+    async fn execute(&self) -> Result<()> {
+        let count = cat_csv_in_file(
+            &self.inputs,
+            &self.output,
+            &self.dialect.with_output_encoding(self.output_encoding),
+        )?;
+        eprintln!("合并完成，共{}个文件{}行", self.inputs.len(), count);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct CsvSplitOptions {
+    /// 输入文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file)]
+    pub input: String,
+
+    /// 每个文件的最大行数
+    #[arg(long, conflicts_with = "column", required_unless_present = "column")]
+    pub rows: Option<usize>,
+
+    /// 按该列的值拆分，值相同的行写入同一个文件，不会覆盖输出目录中已存在的文件
+    #[arg(long)]
+    pub column: Option<String>,
+
+    /// 输出目录，不存在时自动创建
+    #[arg(long, default_value = ".")]
+    pub out_dir: PathBuf,
+
+    /// 输出文件名的前缀，默认为输入的文件名
+    #[arg(long)]
+    pub prefix: Option<String>,
+
+    /// 输出文件的编码，可选 utf-8、utf-8-bom、utf-16le、utf-16be、gbk、gb18030
+    #[arg(long, default_value = "utf-8")]
+    pub output_encoding: CsvEncoding,
+
+    #[command(flatten)]
+    pub dialect: CsvDialectArgs,
+}
+
+impl CmdExecutor for CsvSplitOptions {
+    async fn execute(&self) -> Result<()> {
+        let by = match (self.rows, &self.column) {
+            (Some(rows), _) => CsvSplitBy::Rows(rows),
+            (_, Some(column)) => CsvSplitBy::Column(column.clone()),
+            _ => return Err(anyhow!("需要指定 --rows 或 --column")),
+        };
+        let paths = split_csv_in_file(
+            &self.input,
+            &self.out_dir,
+            self.prefix.as_deref(),
+            &by,
+            &self.dialect.with_output_encoding(self.output_encoding),
+        )?;
+        for path in &paths {
+            println!("{}", path.display());
+        }
+        eprintln!("拆分完成，共{}个文件", paths.len());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct CsvDedupOptions {
+    /// 输入文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file)]
+    pub input: String,
+
+    /// 判断重复的键列，如 id 或 name,birthday，默认比较整行
+    #[arg(short, long, value_delimiter = ',')]
+    pub key: Vec<String>,
+
+    /// 输出文件路径,“-”为输出到标准输出
+    #[arg(short, long, default_value = "-")]
+    pub output: String,
+
+    /// 输出文件的编码，可选 utf-8、utf-8-bom、utf-16le、utf-16be、gbk、gb18030
+    #[arg(long, default_value = "utf-8")]
+    pub output_encoding: CsvEncoding,
+
+    #[command(flatten)]
+    pub dialect: CsvDialectArgs,
+}
+
+impl CmdExecutor for CsvDedupOptions {
+    async fn execute(&self) -> Result<()> {
+        let (kept, removed) = dedup_csv_in_file(
+            &self.input,
+            &self.output,
+            &self.key,
+            &self.dialect.with_output_encoding(self.output_encoding),
+        )?;
+        eprintln!("去重完成，保留{}行，删除{}行", kept, removed);
+        Ok(())
+    }
+}
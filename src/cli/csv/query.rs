@@ -0,0 +1,74 @@
+use super::{parse_column_type, parse_csv_format_value, CsvDialectArgs};
+use crate::{
+    query_csv_in_file, utils::verify_file, CmdExecutor, CsvColumnType, CsvConvertConfig,
+    CsvFormatType, CsvTable,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::Path;
+
+// 解析 [表名=]路径 形式的参数，未指定表名时使用文件名
+fn parse_table(s: &str) -> Result<CsvTable, String> {
+    let (name, path) = match s.split_once('=') {
+        Some((name, path)) if !name.trim().is_empty() => (name.trim().to_string(), path),
+        Some(_) => return Err(format!("表名不能为空: {}", s)),
+        None => {
+            let name = Path::new(s)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .filter(|stem| *stem != "-")
+                .ok_or_else(|| format!("无法从路径获取表名，请使用 表名=路径 指定: {}", s))?;
+            (name.to_string(), s)
+        }
+    };
+    Ok(CsvTable {
+        name,
+        path: verify_file(path)?,
+    })
+}
+
+#[derive(Debug, Parser)]
+pub struct CsvQueryOptions {
+    /// 查询语句，如 "SELECT Nationality, count(*) FROM juventus GROUP BY Nationality"
+    pub sql: String,
+
+    /// 作为表加载的csv文件，格式为 [表名=]路径，默认表名为文件名，可指定多个
+    #[arg(short, long = "table", value_parser=parse_table, required = true)]
+    pub tables: Vec<CsvTable>,
+
+    /// 输出文件路径,“-”为输出到标准输出
+    #[arg(short, long, default_value = "-")]
+    pub output: String,
+
+    /// 输出格式，可选 json、yaml、toml、ndjson、xml、md、html、msgpack、cbor，默认输出为终端表格
+    #[arg(short, long, value_parser=parse_csv_format_value)]
+    pub format: Option<CsvFormatType>,
+
+    /// 不推断字段类型，所有字段按字符串加载
+    #[arg(long, default_value_t = false)]
+    pub no_infer: bool,
+
+    /// 指定列类型，如 age=int,active=bool，可选 string、int、float、bool、date，作用于所有包含该列的表
+    #[arg(long = "type", value_parser=parse_column_type, value_delimiter = ',')]
+    pub types: Vec<(String, CsvColumnType)>,
+
+    /// toml输出的根表名
+    #[arg(long, default_value = "data")]
+    pub toml_key: String,
+
+    #[command(flatten)]
+    pub dialect: CsvDialectArgs,
+}
+
+impl CmdExecutor for CsvQueryOptions {
+    async fn execute(&self) -> Result<()> {
+        let config = CsvConvertConfig {
+            infer_types: !self.no_infer,
+            column_types: self.types.iter().cloned().collect(),
+            toml_key: self.toml_key.clone(),
+            dialect: (&self.dialect).into(),
+            ..Default::default()
+        };
+        query_csv_in_file(&self.tables, &self.sql, &self.output, self.format, &config)
+    }
+}
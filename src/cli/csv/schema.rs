@@ -0,0 +1,68 @@
+use super::CsvDialectArgs;
+use crate::{
+    schema_csv_in_file, utils::verify_file, validate_csv_in_file, CmdExecutor, CsvSchemaConfig,
+};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+pub struct CsvSchemaOptions {
+    /// 需要推断的csv文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file, default_value = "-")]
+    pub input: String,
+
+    /// 输出文件路径,“-”为输出到标准输出
+    #[arg(short, long, default_value = "-")]
+    pub output: String,
+
+    /// 用于推断的最大行数
+    #[arg(long, default_value_t = 1000)]
+    pub sample: usize,
+
+    /// 不同值不超过该数量的字符串列输出为enum
+    #[arg(long, default_value_t = 10)]
+    pub max_enum: usize,
+
+    #[command(flatten)]
+    pub dialect: CsvDialectArgs,
+}
+
+impl CmdExecutor for CsvSchemaOptions {
+    async fn execute(&self) -> Result<()> {
+        let config = CsvSchemaConfig {
+            dialect: (&self.dialect).into(),
+            sample: self.sample,
+            max_enum: self.max_enum,
+        };
+        schema_csv_in_file(&self.input, &self.output, &config)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct CsvValidateOptions {
+    /// 需要校验的csv文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file, default_value = "-")]
+    pub input: String,
+
+    /// JSON Schema文件路径
+    #[arg(short, long, value_parser=verify_file)]
+    pub schema: String,
+
+    #[command(flatten)]
+    pub dialect: CsvDialectArgs,
+}
+
+impl CmdExecutor for CsvValidateOptions {
+    async fn execute(&self) -> Result<()> {
+        let report = validate_csv_in_file(&self.input, &self.schema, &(&self.dialect).into())?;
+        for error in &report.errors {
+            eprintln!("{}", error);
+        }
+        if !report.errors.is_empty() {
+            return Err(anyhow!("校验失败，共{}处错误", report.errors.len()));
+        }
+
+        println!("校验通过，共{}行", report.rows);
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use super::CsvDialectArgs;
+use crate::{stats_csv_in_file, utils::verify_file, CmdExecutor, CsvStatsConfig};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+pub struct CsvStatsOptions {
+    /// 需要统计的csv文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file, default_value = "-")]
+    pub input: String,
+
+    /// 输出文件路径,“-”为输出到标准输出
+    #[arg(short, long, default_value = "-")]
+    pub output: String,
+
+    /// 以json格式输出，便于在CI中检查
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+
+    /// 输出每列出现次数最多的前N个值
+    #[arg(long, default_value_t = 5)]
+    pub top: usize,
+
+    /// 数值列直方图的分组数
+    #[arg(long, default_value_t = 10)]
+    pub bins: usize,
+
+    #[command(flatten)]
+    pub dialect: CsvDialectArgs,
+}
+
+impl CmdExecutor for CsvStatsOptions {
+    async fn execute(&self) -> Result<()> {
+        let config = CsvStatsConfig {
+            dialect: (&self.dialect).into(),
+            top: self.top,
+            bins: self.bins,
+        };
+        stats_csv_in_file(&self.input, &self.output, &config, self.json)
+    }
+}
@@ -0,0 +1,99 @@
+use super::CsvDialectArgs;
+use crate::{
+    utils::{get_reader_from_path, verify_file},
+    view_csv, view_csv_in_file, CmdExecutor, CsvViewConfig, TableStyle,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::{
+    env,
+    io::{stdout, IsTerminal, Write},
+    process::{Command, Stdio},
+};
+
+#[derive(Debug, Parser)]
+pub struct CsvViewOptions {
+    /// 需要查看的csv或json对象数组文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file, default_value = "-")]
+    pub input: String,
+
+    /// 只显示指定的列，如 name,age
+    #[arg(short, long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// 最多显示的列数，0为不限制
+    #[arg(long, default_value_t = 0)]
+    pub max_columns: usize,
+
+    /// 单元格的最大显示宽度，超出部分截断，0为不截断
+    #[arg(short = 'w', long, default_value_t = 40)]
+    pub max_width: usize,
+
+    /// 显示的页码，从1开始
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub page: u64,
+
+    /// 每页的行数，0为显示全部
+    #[arg(short = 'n', long, default_value_t = 50)]
+    pub page_size: usize,
+
+    /// 不推断字段类型，数字不右对齐
+    #[arg(long, default_value_t = false)]
+    pub no_infer: bool,
+
+    /// 使用ASCII字符绘制边框
+    #[arg(long, default_value_t = false)]
+    pub ascii: bool,
+
+    /// 不使用分页程序，默认输出到终端时使用 $PAGER 或 less 显示
+    #[arg(long, default_value_t = false)]
+    pub no_pager: bool,
+
+    #[command(flatten)]
+    pub dialect: CsvDialectArgs,
+}
+
+impl CmdExecutor for CsvViewOptions {
+    async fn execute(&self) -> Result<()> {
+        let config = CsvViewConfig {
+            dialect: (&self.dialect).into(),
+            infer_types: !self.no_infer,
+            columns: self.columns.clone(),
+            max_columns: self.max_columns,
+            max_width: self.max_width,
+            page: self.page as usize,
+            page_size: self.page_size,
+            style: if self.ascii {
+                TableStyle::Ascii
+            } else {
+                TableStyle::Unicode
+            },
+        };
+        if self.no_pager || !stdout().is_terminal() {
+            return view_csv_in_file(&self.input, "-", &config);
+        }
+
+        let mut output = Vec::new();
+        view_csv(get_reader_from_path(&self.input)?, &mut output, &config)?;
+        // less -F 在内容不超过一屏时直接输出，-S 不折行，左右滚动查看较宽的表格
+        let pager = env::var("PAGER").unwrap_or_else(|_| "less -SRFX".to_string());
+        let mut args = pager.split_whitespace();
+        let child = args.next().and_then(|program| {
+            Command::new(program)
+                .args(args)
+                .stdin(Stdio::piped())
+                .spawn()
+                .ok()
+        });
+        // 没有可用的分页程序时直接输出
+        let Some(mut child) = child else {
+            return Ok(stdout().write_all(&output)?);
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            // 分页程序提前退出时忽略写入错误
+            let _ = stdin.write_all(&output);
+        }
+        child.wait()?;
+        Ok(())
+    }
+}
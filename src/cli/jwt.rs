@@ -0,0 +1,162 @@
+use crate::{
+    get_string_from_path,
+    process::{decode_jwt, format_jwt, sign_jwt, verify_jwt, JwtValidation},
+    utils::verify_file,
+    CmdExecutor,
+};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+use serde_json::Value;
+use std::{fmt::Display, str::FromStr};
+
+#[derive(Debug, Subcommand)]
+pub enum JwtSubCommand {
+    #[command(about = "解码JWT，格式化输出header及claims")]
+    Decode(JwtDecodeOptions),
+    #[command(about = "使用claims json文件生成HS256或EdDSA签名的JWT")]
+    Sign(JwtSignOptions),
+    #[command(about = "验证JWT的签名、过期时间、aud及iss")]
+    Verify(JwtVerifyOptions),
+}
+
+impl CmdExecutor for JwtSubCommand {
+    async fn execute(&self) -> Result<()> {
+        match self {
+            JwtSubCommand::Decode(opts) => opts.execute().await,
+            JwtSubCommand::Sign(opts) => opts.execute().await,
+            JwtSubCommand::Verify(opts) => opts.execute().await,
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct JwtDecodeOptions {
+    /// JWT所在的文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file, default_value = "-")]
+    pub input: String,
+}
+
+impl CmdExecutor for JwtDecodeOptions {
+    async fn execute(&self) -> Result<()> {
+        let jwt = decode_jwt(&get_string_from_path(&self.input)?)?;
+        print!("{}", format_jwt(&jwt, Utc::now().timestamp())?);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct JwtSignOptions {
+    /// claims json文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file, default_value = "-")]
+    pub input: String,
+
+    /// 密钥文件路径，HS256可使用 text generate 生成的blake3.txt，EdDSA使用ed25519.sk
+    #[arg(short, long, value_parser=verify_file)]
+    pub key: String,
+
+    /// 签名的算法
+    #[arg(short, long, value_parser=parse_jwt_algorithm, default_value = "HS256")]
+    pub alg: JwtAlgorithm,
+
+    /// 有效期（秒），指定时以当前时间写入iat及exp声明
+    #[arg(short, long)]
+    pub expires_in: Option<u64>,
+}
+
+impl CmdExecutor for JwtSignOptions {
+    async fn execute(&self) -> Result<()> {
+        let mut claims: Value = serde_json::from_str(&get_string_from_path(&self.input)?)
+            .map_err(|e| anyhow!("claims不是有效的json: {}", e))?;
+        if let (Some(expires_in), Some(map)) = (self.expires_in, claims.as_object_mut()) {
+            let now = Utc::now().timestamp();
+            map.insert("iat".to_string(), now.into());
+            let exp = i64::try_from(expires_in)
+                .ok()
+                .and_then(|expires_in| now.checked_add(expires_in))
+                .ok_or_else(|| anyhow!("有效期过大: {}", expires_in))?;
+            map.insert("exp".to_string(), exp.into());
+        }
+        println!("{}", sign_jwt(&claims, &self.key, self.alg)?);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct JwtVerifyOptions {
+    /// JWT所在的文件路径,“-”为从标准输入读取
+    #[arg(short, long, value_parser=verify_file, default_value = "-")]
+    pub input: String,
+
+    /// 密钥文件路径，HS256使用签名时的密钥文件，EdDSA使用ed25519.pk
+    #[arg(short, long, value_parser=verify_file)]
+    pub key: String,
+
+    /// 签名的算法，header中的算法必须与之一致
+    #[arg(short, long, value_parser=parse_jwt_algorithm, default_value = "HS256")]
+    pub alg: JwtAlgorithm,
+
+    /// 期望的aud声明
+    #[arg(long)]
+    pub aud: Option<String>,
+
+    /// 期望的iss声明
+    #[arg(long)]
+    pub iss: Option<String>,
+
+    /// 校验exp、nbf时允许的时钟误差（秒）
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(i64).range(0..))]
+    pub leeway: i64,
+}
+
+impl CmdExecutor for JwtVerifyOptions {
+    async fn execute(&self) -> Result<()> {
+        let validation = JwtValidation {
+            audience: self.aud.clone(),
+            issuer: self.iss.clone(),
+            leeway: self.leeway,
+            now: Utc::now().timestamp(),
+        };
+        let token = get_string_from_path(&self.input)?;
+        let jwt = verify_jwt(&token, &self.key, self.alg, &validation)?;
+        println!("验证通过");
+        print!("{}", format_jwt(&jwt, validation.now)?);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+    Hs256,
+    EdDsa,
+}
+
+impl From<JwtAlgorithm> for &'static str {
+    fn from(value: JwtAlgorithm) -> Self {
+        match value {
+            JwtAlgorithm::Hs256 => "HS256",
+            JwtAlgorithm::EdDsa => "EdDSA",
+        }
+    }
+}
+
+impl FromStr for JwtAlgorithm {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "hs256" => Ok(JwtAlgorithm::Hs256),
+            "eddsa" | "ed25519" => Ok(JwtAlgorithm::EdDsa),
+            _ => Err(format!("Invalid jwt algorithm: {}", s)),
+        }
+    }
+}
+
+impl Display for JwtAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
+
+fn parse_jwt_algorithm(s: &str) -> Result<JwtAlgorithm, String> {
+    s.parse()
+}
@@ -1,17 +1,28 @@
 mod base64;
+mod codec;
+mod convert;
 mod csv;
 mod gen_pass;
 mod http;
+mod jwt;
 mod text;
 
 use anyhow::Result;
 pub use base64::Base64FormatType;
-use clap::{command, Parser, Subcommand};
-pub use csv::{CsvFormatType, CsvOptions};
+use clap::{Parser, Subcommand};
+pub use codec::CodecType;
+pub use convert::{ConvertFormatType, ConvertOptions, CsvQuoteStyle};
+pub use csv::{CsvColumnType, CsvDiffFormat, CsvEncoding, CsvFormatType, CsvJoinKind, CsvOptions};
+pub use jwt::JwtAlgorithm;
 pub use text::{TextSignFormatType, TextSignOption};
 
 use self::{
-    base64::Base64SubCommand, gen_pass::GenPassOptions, http::HttpSubCommand, text::TextSubCommand,
+    base64::Base64SubCommand,
+    codec::{DecodeOptions, EncodeOptions},
+    gen_pass::GenPassOptions,
+    http::HttpSubCommand,
+    jwt::JwtSubCommand,
+    text::TextSubCommand,
 };
 
 #[allow(async_fn_in_trait)]
@@ -21,16 +32,24 @@ pub trait CmdExecutor {
 
 #[derive(Subcommand)]
 pub enum RCliCommand {
-    #[command(about = "转换csv文件内容到json、yaml、toml")]
-    Csv(CsvOptions),
+    #[command(about = "转换csv文件内容到json、yaml、toml等格式")]
+    Csv(Box<CsvOptions>),
+    #[command(about = "在json、yaml、toml、csv格式之间互相转换")]
+    Convert(ConvertOptions),
     #[command(name = "genpass", about = "转换csv文件内容到json、yaml、toml")]
     GenPass(GenPassOptions),
     #[command(subcommand)]
     Base64(Base64SubCommand),
+    #[command(about = "使用hex、base32、base58、ascii85等方式编码")]
+    Encode(EncodeOptions),
+    #[command(about = "使用hex、base32、base58、ascii85等方式解码")]
+    Decode(DecodeOptions),
     #[command(subcommand)]
     Text(TextSubCommand),
     #[command(subcommand)]
     Http(HttpSubCommand),
+    #[command(subcommand, about = "解码、签名及验证JWT")]
+    Jwt(JwtSubCommand),
 }
 
 #[derive(Parser)]
@@ -44,10 +63,14 @@ impl CmdExecutor for Cli {
     async fn execute(&self) -> Result<()> {
         match &self.subcommand {
             RCliCommand::Csv(opt) => opt.execute().await,
+            RCliCommand::Convert(opt) => opt.execute().await,
             RCliCommand::GenPass(opt) => opt.execute().await,
             RCliCommand::Base64(sub_cmd) => sub_cmd.execute().await,
+            RCliCommand::Encode(opt) => opt.execute().await,
+            RCliCommand::Decode(opt) => opt.execute().await,
             RCliCommand::Text(sub_cmd) => sub_cmd.execute().await,
             RCliCommand::Http(sub_cmd) => sub_cmd.execute().await,
+            RCliCommand::Jwt(sub_cmd) => sub_cmd.execute().await,
         }
     }
 }
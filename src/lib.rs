@@ -3,10 +3,22 @@ mod process;
 mod utils;
 
 pub use cli::{
-    Base64FormatType, Cli, CmdExecutor, CsvFormatType, RCliCommand, TextSignFormatType,
-    TextSignOption,
+    Base64FormatType, Cli, CmdExecutor, CodecType, ConvertFormatType, CsvColumnType, CsvDiffFormat,
+    CsvEncoding, CsvFormatType, CsvJoinKind, CsvQuoteStyle, JwtAlgorithm, RCliCommand,
+    TextSignFormatType, TextSignOption,
 };
 pub use process::{
-    convert_csv_in_file, gen_pass, generate_key, http_serve, sign_text, verify_text,
+    cat_csv_in_file, convert_csv_batch, convert_csv_batch_files, convert_csv_in_file,
+    convert_csv_stream, convert_in_file, decode_base64, decode_base64_stream, decode_in_file,
+    decode_jwt, dedup_csv_in_file, diff_csv_in_file, encode_base64, encode_base64_stream,
+    encode_data_uri, encode_in_file, format_jwt, gen_pass, generate_key, get_codec, glob_files,
+    http_serve, is_glob_pattern, join_csv_in_file, mask_csv_in_file, parse_data_uri,
+    query_csv_in_file, schema_csv_in_file, sign_jwt, sign_text, sniff_mime, split_csv_in_file,
+    stats_csv_in_file, validate_csv_in_file, verify_jwt, verify_text, view_csv, view_csv_in_file,
+    watch_csv_inputs, Base64DecodeMode, Base64LineWrap, Codec, ConvertConfig, CsvBatchConfig,
+    CsvBatchReport, CsvConvertConfig, CsvDialect, CsvFakeKind, CsvMaskConfig, CsvMaskKey,
+    CsvMaskRule, CsvMaskStrategy, CsvSchemaConfig, CsvSortKey, CsvSplitBy, CsvStatsConfig,
+    CsvTable, CsvValidationError, CsvValidationReport, CsvViewConfig, DataUri, Jwt, JwtValidation,
+    TableStyle,
 };
-pub use utils::{get_string_from_path, save_str_in_file, verify_dir};
+pub use utils::{get_string_from_path, is_broken_pipe, save_str_in_file, verify_dir};
@@ -1,12 +1,14 @@
 use anyhow::Result;
 use clap::Parser;
-use rrcli::{Cli, CmdExecutor};
+use rrcli::{is_broken_pipe, Cli, CmdExecutor};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
     let cli = Cli::parse();
-    cli.execute().await?;
-
-    Ok(())
+    match cli.execute().await {
+        // 下游提前关闭管道时（如 | head）不再需要输出，正常退出
+        Err(e) if is_broken_pipe(&e) => Ok(()),
+        result => result,
+    }
 }
@@ -1,11 +1,28 @@
 mod process_base64;
+mod process_codec;
+mod process_convert;
 mod process_csv;
 mod process_gen_pass;
 mod process_http;
+mod process_jwt;
 mod process_text;
 
-pub use process_base64::{decode_base64, encode_base64};
-pub use process_csv::convert_csv_in_file;
+pub use process_base64::{
+    decode_base64, decode_base64_stream, encode_base64, encode_base64_stream, encode_data_uri,
+    parse_data_uri, sniff_mime, Base64DecodeMode, Base64LineWrap, DataUri,
+};
+pub use process_codec::{decode_in_file, encode_in_file, get_codec, Codec};
+pub use process_convert::{convert_in_file, ConvertConfig};
+pub use process_csv::{
+    cat_csv_in_file, convert_csv_batch, convert_csv_batch_files, convert_csv_in_file,
+    convert_csv_stream, dedup_csv_in_file, diff_csv_in_file, glob_files, is_glob_pattern,
+    join_csv_in_file, mask_csv_in_file, query_csv_in_file, schema_csv_in_file, split_csv_in_file,
+    stats_csv_in_file, validate_csv_in_file, view_csv, view_csv_in_file, watch_csv_inputs,
+    CsvBatchConfig, CsvBatchReport, CsvConvertConfig, CsvDialect, CsvFakeKind, CsvMaskConfig,
+    CsvMaskKey, CsvMaskRule, CsvMaskStrategy, CsvSchemaConfig, CsvSortKey, CsvSplitBy,
+    CsvStatsConfig, CsvTable, CsvValidationError, CsvValidationReport, CsvViewConfig, TableStyle,
+};
 pub use process_gen_pass::gen_pass;
 pub use process_http::http_serve;
+pub use process_jwt::{decode_jwt, format_jwt, sign_jwt, verify_jwt, Jwt, JwtValidation};
 pub use process_text::{generate_key, sign_text, verify_text};
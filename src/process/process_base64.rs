@@ -1,53 +1,386 @@
 use crate::Base64FormatType;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use base64::{
-    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    alphabet::{self, Alphabet},
+    engine::{general_purpose::STANDARD, DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    read::DecoderReader,
+    write::EncoderWriter,
     Engine,
 };
+use percent_encoding::percent_decode_str;
+use std::io::{self, Read, Write};
 
-pub fn encode_base64(input: &str, format: Base64FormatType) -> Result<String> {
-    let bytes = input.as_bytes().to_vec();
+// 解码时对输入的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64DecodeMode {
+    // 只忽略换行符，填充必须符合格式
+    Strict,
+    // 忽略所有空白字符，允许缺少或多余的填充
+    Lenient,
+    // 在宽松模式的基础上自动识别标准及URL安全字母表
+    Auto,
+}
+
+// 编码结果的折行方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base64LineWrap {
+    pub width: usize,
+    pub crlf: bool,
+}
+
+impl Base64LineWrap {
+    // MIME (RFC 2045) 要求每行不超过76个字符，使用CRLF换行
+    pub const MIME: Self = Self {
+        width: 76,
+        crlf: true,
+    };
+}
+
+fn alphabet(format: Base64FormatType) -> &'static Alphabet {
+    match format {
+        Base64FormatType::UrlSafe | Base64FormatType::UrlSafePad => &alphabet::URL_SAFE,
+        Base64FormatType::Standard | Base64FormatType::StandardNoPad => &alphabet::STANDARD,
+        Base64FormatType::Bcrypt => &alphabet::BCRYPT,
+        Base64FormatType::Crypt => &alphabet::CRYPT,
+    }
+}
+
+fn engine(format: Base64FormatType, mode: Base64DecodeMode) -> GeneralPurpose {
+    let padding = matches!(
+        format,
+        Base64FormatType::Standard | Base64FormatType::UrlSafePad
+    );
+    let decode_padding = match mode {
+        Base64DecodeMode::Strict if padding => DecodePaddingMode::RequireCanonical,
+        Base64DecodeMode::Strict => DecodePaddingMode::RequireNone,
+        // 宽松模式下填充字符已被去除
+        Base64DecodeMode::Lenient | Base64DecodeMode::Auto => DecodePaddingMode::RequireNone,
+    };
+    let alphabet = match mode {
+        // 自动识别时URL安全字母表的字符已转换为标准字母表
+        Base64DecodeMode::Auto => &alphabet::STANDARD,
+        _ => alphabet(format),
+    };
+    let config = GeneralPurposeConfig::new()
+        .with_encode_padding(padding)
+        .with_decode_padding_mode(decode_padding);
+    GeneralPurpose::new(alphabet, config)
+}
+
+pub fn encode_base64(input: &[u8], format: Base64FormatType) -> Result<String> {
+    Ok(engine(format, Base64DecodeMode::Strict).encode(input))
+}
+
+// 忽略首尾的空白字符，如文件末尾的换行
+pub fn decode_base64(input: &[u8], format: Base64FormatType) -> Result<Vec<u8>> {
+    Ok(engine(format, Base64DecodeMode::Strict).decode(input.trim_ascii())?)
+}
+
+// 分块读取并编码，不需要将全部内容读入内存，返回读取的字节数
+pub fn encode_base64_stream(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    format: Base64FormatType,
+    wrap: Option<Base64LineWrap>,
+) -> Result<u64> {
+    let engine = engine(format, Base64DecodeMode::Strict);
+    let size = match wrap {
+        Some(wrap) if wrap.width > 0 => {
+            let mut writer = LineWrapWriter::new(writer, wrap);
+            let mut encoder = EncoderWriter::new(&mut writer, &engine);
+            let size = io::copy(reader, &mut encoder)?;
+            encoder.finish()?;
+            size
+        }
+        _ => {
+            let mut encoder = EncoderWriter::new(writer, &engine);
+            let size = io::copy(reader, &mut encoder)?;
+            encoder.finish()?;
+            size
+        }
+    };
+    Ok(size)
+}
+
+// 分块读取并解码，返回写入的字节数
+pub fn decode_base64_stream(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    format: Base64FormatType,
+    mode: Base64DecodeMode,
+) -> Result<u64> {
+    let engine = engine(format, mode);
+    let mut decoder = DecoderReader::new(DecodeFilter { reader, mode }, &engine);
+    Ok(io::copy(&mut decoder, writer)?)
+}
+
+// data URI的MIME类型及解码后的内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUri {
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+// 文件开头的特征字节及对应的MIME类型，偏移为特征字节在文件中的位置
+const MAGIC_BYTES: [(usize, &[u8], &str); 11] = [
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (0, b"\x00\x00\x01\x00", "image/x-icon"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"wOFF", "font/woff"),
+    (0, b"wOF2", "font/woff2"),
+    (0, b"\x00\x01\x00\x00", "font/ttf"),
+    (0, b"OTTO", "font/otf"),
+    (0, b"PK\x03\x04", "application/zip"),
+];
+
+// 较短的特征字节，文本文件也可能以此开头（如以 BM 开头的txt），只在扩展名无法识别时使用
+const SHORT_MAGIC_BYTES: [(&[u8], &str); 3] = [
+    (b"BM", "image/bmp"),
+    (b"ID3", "audio/mpeg"),
+    (b"\x1f\x8b", "application/gzip"),
+];
+
+// ISO媒体文件 ftyp 之后的主品牌对应的MIME类型
+const FTYP_BRANDS: [(&[u8], &str); 16] = [
+    (b"avif", "image/avif"),
+    (b"avis", "image/avif"),
+    (b"heic", "image/heic"),
+    (b"heix", "image/heic"),
+    (b"heim", "image/heic"),
+    (b"heis", "image/heic"),
+    (b"mif1", "image/heif"),
+    (b"msf1", "image/heif"),
+    (b"M4A ", "audio/mp4"),
+    (b"M4B ", "audio/mp4"),
+    (b"qt  ", "video/quicktime"),
+    (b"isom", "video/mp4"),
+    (b"iso2", "video/mp4"),
+    (b"mp41", "video/mp4"),
+    (b"mp42", "video/mp4"),
+    (b"M4V ", "video/mp4"),
+];
+
+// 根据文件开头的特征字节识别MIME类型，无法识别时根据扩展名判断，较短的特征字节最后才使用
+pub fn sniff_mime(data: &[u8], path: &str) -> &'static str {
+    for (offset, magic, mime) in MAGIC_BYTES {
+        if data.get(offset..offset + magic.len()) == Some(magic) {
+            return mime;
+        }
+    }
+    // RIFF容器还可能是wav、avi，需同时检查格式标识
+    if data.get(..4) == Some(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        return "image/webp";
+    }
+    // ISO媒体文件（mp4、avif、heic、m4a等）根据 ftyp 之后的品牌区分
+    if data.get(4..8) == Some(b"ftyp") {
+        let brand = data.get(8..12);
+        if let Some((_, mime)) = FTYP_BRANDS.iter().find(|(b, _)| Some(*b) == brand) {
+            return mime;
+        }
+    }
+    // svg为文本格式，检查开头的内容
+    let head = String::from_utf8_lossy(&data[..data.len().min(512)]);
+    if head.trim_start().starts_with("<svg") || (head.contains("<?xml") && head.contains("<svg")) {
+        return "image/svg+xml";
+    }
+
+    if let Some(mime) = mime_from_extension(path) {
+        return mime;
+    }
+    SHORT_MAGIC_BYTES
+        .iter()
+        .find(|(magic, _)| data.starts_with(magic))
+        .map_or("application/octet-stream", |(_, mime)| mime)
+}
+
+fn mime_from_extension(path: &str) -> Option<&'static str> {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let mime = match extension.as_deref()? {
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "html" | "htm" => "text/html",
+        "txt" => "text/plain",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+// 生成 data:<mime>;base64,... 形式的URI，使用带填充的标准base64
+pub fn encode_data_uri(data: &[u8], mime: &str) -> String {
+    format!("data:{};base64,{}", mime, STANDARD.encode(data))
+}
+
+// 解析data URI (RFC 2397)，未声明MIME类型时为 text/plain;charset=US-ASCII
+pub fn parse_data_uri(uri: &str) -> Result<DataUri> {
+    let uri = uri.trim();
+    let rest = uri
+        .get(..5)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("data:"))
+        .map(|_| &uri[5..])
+        .ok_or_else(|| anyhow!("data URI应以 data: 开头"))?;
+    let (meta, content) = rest
+        .split_once(',')
+        .ok_or_else(|| anyhow!("data URI缺少 , 分隔的内容"))?;
 
-    let encode_str = match format {
-        Base64FormatType::UrlSafe => URL_SAFE_NO_PAD.encode(bytes),
-        Base64FormatType::Standard => STANDARD.encode(bytes),
+    let (mime, is_base64) = match meta.strip_suffix(";base64") {
+        Some(mime) => (mime, true),
+        None => (meta, false),
+    };
+    let mime = match mime {
+        "" => "text/plain;charset=US-ASCII".to_string(),
+        mime if mime.starts_with(';') => format!("text/plain{}", mime),
+        mime => mime.to_string(),
     };
+    // 内容中可能包含百分号编码，base64内容中可能包含换行
+    let content: Vec<u8> = percent_decode_str(content).collect();
+    let data = if is_base64 {
+        let mut data = Vec::new();
+        decode_base64_stream(
+            &mut content.as_slice(),
+            &mut data,
+            Base64FormatType::Standard,
+            Base64DecodeMode::Auto,
+        )?;
+        data
+    } else {
+        content
+    };
+    Ok(DataUri { mime, data })
+}
+
+// 每写入指定数量的字符后插入换行，最后一行之后不换行
+struct LineWrapWriter<W> {
+    writer: W,
+    wrap: Base64LineWrap,
+    column: usize,
+}
+
+impl<W: Write> LineWrapWriter<W> {
+    fn new(writer: W, wrap: Base64LineWrap) -> Self {
+        Self {
+            writer,
+            wrap,
+            column: 0,
+        }
+    }
+}
 
-    Ok(encode_str)
+impl<W: Write> Write for LineWrapWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        while !rest.is_empty() {
+            if self.column == self.wrap.width {
+                let ending: &[u8] = if self.wrap.crlf { b"\r\n" } else { b"\n" };
+                self.writer.write_all(ending)?;
+                self.column = 0;
+            }
+            let len = rest.len().min(self.wrap.width - self.column);
+            self.writer.write_all(&rest[..len])?;
+            self.column += len;
+            rest = &rest[len..];
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
 }
 
-pub fn decode_base64(input: &str, format: Base64FormatType) -> Result<String> {
-    let decode_str = match format {
-        Base64FormatType::UrlSafe => URL_SAFE_NO_PAD.decode(input),
-        Base64FormatType::Standard => STANDARD.decode(input),
-    }?;
+// 按解码方式去除读取内容中的换行符、空白字符及填充
+struct DecodeFilter<R> {
+    reader: R,
+    mode: Base64DecodeMode,
+}
 
-    Ok(String::from_utf8(decode_str)?)
+impl<R: Read> Read for DecodeFilter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let size = self.reader.read(buf)?;
+            if size == 0 {
+                return Ok(0);
+            }
+            let mut len = 0;
+            for i in 0..size {
+                let b = match (self.mode, buf[i]) {
+                    (_, b'\n' | b'\r') => continue,
+                    (Base64DecodeMode::Strict, b) => b,
+                    (_, b'=') => continue,
+                    (_, b) if b.is_ascii_whitespace() => continue,
+                    (Base64DecodeMode::Auto, b'-') => b'+',
+                    (Base64DecodeMode::Auto, b'_') => b'/',
+                    (_, b) => b,
+                };
+                buf[len] = b;
+                len += 1;
+            }
+            // 读取的内容全部被去除时继续读取，返回0表示已读完
+            if len > 0 {
+                return Ok(len);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn decode_stream(
+        input: &[u8],
+        format: Base64FormatType,
+        mode: Base64DecodeMode,
+    ) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        decode_base64_stream(&mut &input[..], &mut output, format, mode)?;
+        Ok(output)
+    }
+
     #[test]
     fn test_decode_base64_url_safe() {
-        let input = "aGVsbG8gd29ybGQ";
+        let input = b"aGVsbG8gd29ybGQ";
         let format = Base64FormatType::UrlSafe;
         let result = decode_base64(input, format).unwrap();
-        assert_eq!(result, "hello world");
+        assert_eq!(result, b"hello world");
     }
 
     #[test]
     fn test_decode_base64_standard() {
-        let input = "aGVsbG8gd29ybGQ=";
+        let input = b"aGVsbG8gd29ybGQ=";
         let format = Base64FormatType::Standard;
         let result = decode_base64(input, format).unwrap();
-        assert_eq!(result, "hello world");
+        assert_eq!(result, b"hello world");
+    }
+
+    // 测试忽略输入末尾的换行
+    #[test]
+    fn test_decode_base64_trailing_newline() {
+        let input = b"aGVsbG8gd29ybGQ=\n";
+        let format = Base64FormatType::Standard;
+        let result = decode_base64(input, format).unwrap();
+        assert_eq!(result, b"hello world");
     }
 
     #[test]
     fn test_encode_base64_url_safe() {
-        let input = "hello world";
+        let input = b"hello world";
         let format = Base64FormatType::UrlSafe;
         let result = encode_base64(input, format).unwrap();
         assert_eq!(result, "aGVsbG8gd29ybGQ");
@@ -55,9 +388,121 @@ mod tests {
 
     #[test]
     fn test_encode_base64_standard() {
-        let input = "hello world";
+        let input = b"hello world";
         let format = Base64FormatType::Standard;
         let result = encode_base64(input, format).unwrap();
         assert_eq!(result, "aGVsbG8gd29ybGQ=");
     }
+
+    #[test]
+    fn test_base64_variants() -> Result<()> {
+        let input = [0xfb, 0xff, 0xbf, 0x68, 0x69];
+        let cases = [
+            (Base64FormatType::Standard, "+/+/aGk="),
+            (Base64FormatType::StandardNoPad, "+/+/aGk"),
+            (Base64FormatType::UrlSafe, "-_-_aGk"),
+            (Base64FormatType::UrlSafePad, "-_-_aGk="),
+            (Base64FormatType::Bcrypt, "8989YEi"),
+            (Base64FormatType::Crypt, "yzyzO4Y"),
+        ];
+        for (format, encoded) in cases {
+            assert_eq!(encode_base64(&input, format)?, encoded, "{}", format);
+            assert_eq!(decode_base64(encoded.as_bytes(), format)?, input);
+        }
+        // 严格模式下填充必须符合格式
+        assert!(decode_base64(b"+/+/aGk", Base64FormatType::Standard).is_err());
+        assert!(decode_base64(b"-_-_aGk=", Base64FormatType::UrlSafe).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_base64_lenient_and_auto() -> Result<()> {
+        let input = [0xfb, 0xff, 0xbf, 0x68, 0x69];
+        let lenient = Base64DecodeMode::Lenient;
+        for encoded in ["+/+/ aGk", "+/+/\taGk==", " +/+/\r\naGk=== "] {
+            let decoded = decode_stream(encoded.as_bytes(), Base64FormatType::Standard, lenient)?;
+            assert_eq!(decoded, input);
+        }
+        assert!(decode_stream(b"-_-_aGk", Base64FormatType::Standard, lenient).is_err());
+
+        // 自动识别时忽略指定的格式
+        for encoded in ["+/+/aGk=", "-_-_aGk", "-_-_\naGk="] {
+            let decoded = decode_stream(
+                encoded.as_bytes(),
+                Base64FormatType::UrlSafe,
+                Base64DecodeMode::Auto,
+            )?;
+            assert_eq!(decoded, input);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_data_uri() -> Result<()> {
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR";
+        assert_eq!(sniff_mime(png, "icon.bin"), "image/png");
+        assert_eq!(
+            sniff_mime(b"RIFF\x00\x00\x00\x00WEBPVP8 ", "a"),
+            "image/webp"
+        );
+        assert_eq!(
+            sniff_mime(b"XXXX\x00\x00\x00\x00WEBP", "a"),
+            "application/octet-stream"
+        );
+        assert_eq!(sniff_mime(b"\x00\x00\x00\x1cftypavif", "a"), "image/avif");
+        assert_eq!(sniff_mime(b"\x00\x00\x00\x18ftypheic", "a"), "image/heic");
+        assert_eq!(sniff_mime(b"\x00\x00\x00\x20ftypM4A ", "a"), "audio/mp4");
+        assert_eq!(sniff_mime(b"\x00\x00\x00\x20ftypisom", "a"), "video/mp4");
+        assert_eq!(
+            sniff_mime(b"<?xml version=\"1.0\"?>\n<svg>", "a"),
+            "image/svg+xml"
+        );
+        assert_eq!(sniff_mime(b"body{}", "style.CSS"), "text/css");
+        assert_eq!(sniff_mime(b"body{}", "style"), "application/octet-stream");
+        assert_eq!(sniff_mime(b"BMW cars", "notes.txt"), "text/plain");
+        assert_eq!(sniff_mime(b"BM{color:red}", "a.css"), "text/css");
+        assert_eq!(sniff_mime(b"BM\x00\x00", "image"), "image/bmp");
+        assert_eq!(sniff_mime(b"\x1f\x8b\x08", "a.gz"), "application/gzip");
+
+        let uri = encode_data_uri(png, "image/png");
+        assert_eq!(uri, "data:image/png;base64,iVBORw0KGgoAAAANSUhEUg==");
+        let parsed = parse_data_uri(&uri)?;
+        assert_eq!(parsed.mime, "image/png");
+        assert_eq!(parsed.data, png);
+
+        let parsed = parse_data_uri("DATA:,a%20b%2C")?;
+        assert_eq!(parsed.mime, "text/plain;charset=US-ASCII");
+        assert_eq!(parsed.data, b"a b,");
+        let parsed = parse_data_uri("data:;charset=utf-8;base64,5Lit\n")?;
+        assert_eq!(parsed.mime, "text/plain;charset=utf-8");
+        assert_eq!(parsed.data, "中".as_bytes());
+
+        assert!(parse_data_uri("image/png;base64,AAAA").is_err());
+        assert!(parse_data_uri("data:image/png;base64").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_base64_stream_binary() -> Result<()> {
+        // 非UTF-8的二进制内容，长度跨越多个读取块
+        let input: Vec<u8> = (0..100_000u32).map(|i| (i * 7 % 256) as u8).collect();
+        for format in [Base64FormatType::UrlSafe, Base64FormatType::Standard] {
+            let mut encoded = Vec::new();
+            let size = encode_base64_stream(&mut input.as_slice(), &mut encoded, format, None)?;
+            assert_eq!(size, input.len() as u64);
+            assert_eq!(encoded, encode_base64(&input, format)?.into_bytes());
+
+            // 按MIME格式折行后仍可解码
+            let mut wrapped = Vec::new();
+            let wrap = Some(Base64LineWrap::MIME);
+            encode_base64_stream(&mut input.as_slice(), &mut wrapped, format, wrap)?;
+            let lines: Vec<&[u8]> = wrapped.split(|&b| b == b'\n').collect();
+            assert!(lines[..lines.len() - 1].iter().all(|line| line.len() == 77));
+            assert_eq!(
+                decode_stream(&wrapped, format, Base64DecodeMode::Strict)?,
+                input
+            );
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,349 @@
+use crate::{
+    process::process_base64::{decode_base64, encode_base64},
+    utils::{get_reader_from_path, get_writer_from_path},
+    CodecType,
+};
+use anyhow::{anyhow, Result};
+use data_encoding::{Encoding, Specification, BASE32, HEXLOWER_PERMISSIVE};
+use percent_encoding::{percent_decode, percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::io::{Read, Write};
+
+// 二进制与文本之间的编码，编码和解码都以字节为输入和输出
+pub trait Codec {
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>>;
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>>;
+}
+
+// 根据编码类型获取对应的编码实现
+pub fn get_codec(codec_type: CodecType) -> Result<Box<dyn Codec>> {
+    Ok(match codec_type {
+        CodecType::Hex => Box::new(DataEncoding(HEXLOWER_PERMISSIVE.clone())),
+        CodecType::Base32 => Box::new(DataEncoding(BASE32.clone())),
+        CodecType::Base32Crockford => Box::new(DataEncoding(crockford()?)),
+        CodecType::Base58 => Box::new(Base58 { check: false }),
+        CodecType::Base58Check => Box::new(Base58 { check: true }),
+        CodecType::Base64(format) => Box::new(Base64(format)),
+        CodecType::Ascii85 => Box::new(Ascii85),
+        CodecType::Z85 => Box::new(Z85),
+        CodecType::Percent => Box::new(Percent),
+    })
+}
+
+// 读取全部输入编码后写出
+pub fn encode_in_file(input_path: &str, save_path: &str, codec_type: CodecType) -> Result<()> {
+    let mut input = Vec::new();
+    get_reader_from_path(input_path)?.read_to_end(&mut input)?;
+    let mut writer = get_writer_from_path(save_path)?;
+    writer.write_all(&get_codec(codec_type)?.encode(&input)?)?;
+    // 输出到终端时以换行结尾
+    if save_path == "-" {
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// 读取全部输入解码后写出，二进制内容应输出到文件
+pub fn decode_in_file(input_path: &str, save_path: &str, codec_type: CodecType) -> Result<()> {
+    let mut input = Vec::new();
+    get_reader_from_path(input_path)?.read_to_end(&mut input)?;
+    let mut writer = get_writer_from_path(save_path)?;
+    writer.write_all(&get_codec(codec_type)?.decode(&input)?)?;
+    writer.flush()?;
+    Ok(())
+}
+
+// data-encoding 支持的编码：hex及base32
+struct DataEncoding(Encoding);
+
+impl Codec for DataEncoding {
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.0.encode(input).into_bytes())
+    }
+
+    // 忽略首尾的空白字符，如文件末尾的换行
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.0.decode(input.trim_ascii())?)
+    }
+}
+
+// Crockford base32：不填充，解码时不区分大小写，I L 视为1，O 视为0
+fn crockford() -> Result<Encoding> {
+    let mut spec = Specification::new();
+    spec.symbols.push_str("0123456789ABCDEFGHJKMNPQRSTVWXYZ");
+    spec.translate.from.push_str("abcdefghjkmnpqrstvwxyziIlLoO");
+    spec.translate.to.push_str("ABCDEFGHJKMNPQRSTVWXYZ111100");
+    Ok(spec.encoding()?)
+}
+
+// 比特币字母表的base58，check为true时附加4字节的双SHA-256校验和
+struct Base58 {
+    check: bool,
+}
+
+impl Codec for Base58 {
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        let encoder = bs58::encode(input);
+        let encoded = if self.check {
+            encoder.with_check().into_vec()
+        } else {
+            encoder.into_vec()
+        };
+        Ok(encoded)
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        let decoder = bs58::decode(input.trim_ascii());
+        let decoded = if self.check {
+            decoder.with_check(None).into_vec()?
+        } else {
+            decoder.into_vec()?
+        };
+        Ok(decoded)
+    }
+}
+
+struct Base64(crate::Base64FormatType);
+
+impl Codec for Base64 {
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Ok(encode_base64(input, self.0)?.into_bytes())
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        decode_base64(input, self.0)
+    }
+}
+
+// 每4个字节按大端序转换为5个85进制的数字
+fn to_base85(group: &[u8]) -> [u8; 5] {
+    let mut bytes = [0; 4];
+    bytes[..group.len()].copy_from_slice(group);
+    let mut value = u32::from_be_bytes(bytes);
+    let mut digits = [0; 5];
+    for digit in digits.iter_mut().rev() {
+        *digit = (value % 85) as u8;
+        value /= 85;
+    }
+    digits
+}
+
+fn from_base85(digits: &[u8; 5]) -> Result<[u8; 4]> {
+    let value = digits
+        .iter()
+        .try_fold(0u32, |value, &digit| {
+            value.checked_mul(85)?.checked_add(digit as u32)
+        })
+        .ok_or_else(|| anyhow!("base85数据组超出范围"))?;
+    Ok(value.to_be_bytes())
+}
+
+// btoa风格的ascii85：全0的数据组编码为 z，末尾不足4字节时只输出需要的字符
+struct Ascii85;
+
+impl Codec for Ascii85 {
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        let mut output = Vec::with_capacity(input.len() / 4 * 5 + 5);
+        for group in input.chunks(4) {
+            if group == [0; 4] {
+                output.push(b'z');
+                continue;
+            }
+            let digits = to_base85(group);
+            output.extend(digits[..group.len() + 1].iter().map(|digit| digit + b'!'));
+        }
+        Ok(output)
+    }
+
+    // 忽略空白字符，支持Adobe风格的 <~ ~> 定界符
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        let input = input.trim_ascii();
+        let input = input.strip_prefix(b"<~").unwrap_or(input);
+        let input = input.strip_suffix(b"~>").unwrap_or(input);
+
+        let mut output = Vec::with_capacity(input.len() / 5 * 4 + 4);
+        let mut digits = [0; 5];
+        let mut len = 0;
+        for &c in input.iter().filter(|c| !c.is_ascii_whitespace()) {
+            match c {
+                b'z' if len == 0 => output.extend([0; 4]),
+                b'!'..=b'u' => {
+                    digits[len] = c - b'!';
+                    len += 1;
+                    if len == 5 {
+                        output.extend(from_base85(&digits)?);
+                        len = 0;
+                    }
+                }
+                c => return Err(anyhow!("无效的ascii85字符: {}", c as char)),
+            }
+        }
+        match len {
+            0 => {}
+            1 => return Err(anyhow!("ascii85数据末尾的字符数不正确")),
+            // 不足5个字符的数据组使用最大值 u 补齐
+            len => {
+                digits[len..].fill(84);
+                output.extend(&from_base85(&digits)?[..len - 1]);
+            }
+        }
+        Ok(output)
+    }
+}
+
+const Z85_ALPHABET: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+// ZeroMQ的Z85 (RFC 32)：输入长度必须为4的倍数
+struct Z85;
+
+impl Codec for Z85 {
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        if !input.len().is_multiple_of(4) {
+            return Err(anyhow!("Z85编码的输入长度必须为4的倍数"));
+        }
+        Ok(input
+            .chunks(4)
+            .flat_map(to_base85)
+            .map(|digit| Z85_ALPHABET[digit as usize])
+            .collect())
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        let input = input.trim_ascii();
+        if !input.len().is_multiple_of(5) {
+            return Err(anyhow!("Z85解码的输入长度必须为5的倍数"));
+        }
+        let mut output = Vec::with_capacity(input.len() / 5 * 4);
+        for group in input.chunks(5) {
+            let mut digits = [0; 5];
+            for (digit, c) in digits.iter_mut().zip(group) {
+                *digit = Z85_ALPHABET
+                    .iter()
+                    .position(|a| a == c)
+                    .ok_or_else(|| anyhow!("无效的Z85字符: {}", *c as char))?
+                    as u8;
+            }
+            output.extend(from_base85(&digits)?);
+        }
+        Ok(output)
+    }
+}
+
+// RFC 3986 中不需要编码的字符：字母、数字及 - . _ ~
+const UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+// URL百分号编码
+struct Percent;
+
+impl Codec for Percent {
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Ok(percent_encode(input, UNRESERVED).to_string().into_bytes())
+    }
+
+    // 忽略末尾的换行，不合法的 % 序列保持原样
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        let input = input.trim_ascii_end();
+        Ok(percent_decode(input).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Base64FormatType;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const CODECS: [CodecType; 10] = [
+        CodecType::Hex,
+        CodecType::Base32,
+        CodecType::Base32Crockford,
+        CodecType::Base58,
+        CodecType::Base58Check,
+        CodecType::Base64(Base64FormatType::Standard),
+        CodecType::Base64(Base64FormatType::UrlSafe),
+        CodecType::Ascii85,
+        CodecType::Z85,
+        CodecType::Percent,
+    ];
+
+    fn encode(codec_type: CodecType, input: &[u8]) -> Result<String> {
+        Ok(String::from_utf8(get_codec(codec_type)?.encode(input)?)?)
+    }
+
+    fn decode(codec_type: CodecType, input: &str) -> Result<Vec<u8>> {
+        get_codec(codec_type)?.decode(input.as_bytes())
+    }
+
+    #[test]
+    fn test_codec_known_values() -> Result<()> {
+        let cases = [
+            (CodecType::Hex, &b"hello"[..], "68656c6c6f"),
+            (CodecType::Base32, b"foobar", "MZXW6YTBOI======"),
+            (CodecType::Base32Crockford, b"foobar", "CSQPYRK1E8"),
+            (CodecType::Base58, b"hello world", "StV1DL6CwTryKyV"),
+            (CodecType::Base58Check, b"\x00hello", "12L5B5yqsf7vwb"),
+            (CodecType::Ascii85, b"hell\0\0\0\0o", "BOu!rzDZ"),
+            (
+                CodecType::Z85,
+                b"\x86\x4F\xD2\x6F\xB5\x59\xF7\x5B",
+                "HelloWorld",
+            ),
+            (
+                CodecType::Percent,
+                "a b/中?".as_bytes(),
+                "a%20b%2F%E4%B8%AD%3F",
+            ),
+        ];
+        for (codec_type, input, encoded) in cases {
+            assert_eq!(encode(codec_type, input)?, encoded, "{}", codec_type);
+            assert_eq!(decode(codec_type, encoded)?, input, "{}", codec_type);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_lenient_decode() -> Result<()> {
+        assert_eq!(decode(CodecType::Hex, "68656C6C6F\n")?, b"hello");
+        assert_eq!(decode(CodecType::Base32Crockford, "csqpyrkie8")?, b"foobar");
+        assert_eq!(decode(CodecType::Ascii85, "<~BOu!r\nDZ~>")?, b"hello");
+        assert_eq!(decode(CodecType::Ascii85, "z")?, [0; 4]);
+
+        assert!(decode(CodecType::Base58Check, "12L5B5yqsf7vwc").is_err());
+        assert!(decode(CodecType::Ascii85, "BOu!rD~").is_err());
+        assert!(encode(CodecType::Z85, b"abc").is_err());
+        assert!(decode(CodecType::Z85, "Hell").is_err());
+        Ok(())
+    }
+
+    // 随机内容编码后再解码应与原内容相同
+    #[test]
+    fn test_codec_round_trip() -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(20240501);
+        for codec_type in CODECS {
+            let codec = get_codec(codec_type)?;
+            for _ in 0..200 {
+                let mut len = rng.gen_range(0..64);
+                if codec_type == CodecType::Z85 {
+                    len -= len % 4;
+                }
+                let mut input = vec![0u8; len];
+                rng.fill(&mut input[..]);
+                // 增加前导0及全0数据组的概率
+                if rng.gen_bool(0.2) {
+                    input
+                        .iter_mut()
+                        .take(rng.gen_range(0..8))
+                        .for_each(|b| *b = 0);
+                }
+                let encoded = codec.encode(&input)?;
+                assert_eq!(codec.decode(&encoded)?, input, "{}", codec_type);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,475 @@
+use anyhow::{anyhow, Result};
+use csv::{ReaderBuilder, WriterBuilder};
+use serde_json::{Map, Value};
+use std::io::{Read, Write};
+
+use super::process_csv::{decode_reader, read_csv_records, CsvConvertConfig, EncodingWriter};
+use crate::{
+    utils::{get_reader_from_path, get_writer_from_path},
+    ConvertFormatType, CsvEncoding, CsvQuoteStyle,
+};
+
+// convert命令的csv及编码配置
+#[derive(Debug, Clone, Copy)]
+pub struct ConvertConfig {
+    pub delimiter: u8,
+    pub quote_style: CsvQuoteStyle,
+    // 输入的编码，未指定时根据BOM及内容自动识别
+    pub encoding: Option<CsvEncoding>,
+    pub output_encoding: CsvEncoding,
+}
+
+impl Default for ConvertConfig {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote_style: CsvQuoteStyle::Necessary,
+            encoding: None,
+            output_encoding: CsvEncoding::Utf8,
+        }
+    }
+}
+
+// 根据文件内容猜测格式，依次尝试json、toml、yaml，最后判断是否为csv
+pub fn detect_format(content: &str) -> Result<ConvertFormatType> {
+    let trimmed = content.trim_start();
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<Value>(trimmed).is_ok()
+    {
+        return Ok(ConvertFormatType::Json);
+    }
+    if toml::from_str::<toml::Table>(content).is_ok_and(|table| !table.is_empty()) {
+        return Ok(ConvertFormatType::Toml);
+    }
+    if serde_yaml::from_str::<Value>(content).is_ok_and(|v| v.is_object() || v.is_array()) {
+        return Ok(ConvertFormatType::Yaml);
+    }
+    if looks_like_csv(content) {
+        return Ok(ConvertFormatType::Csv);
+    }
+    Err(anyhow!("无法识别输入内容的格式，请使用 --from 指定"))
+}
+
+// 多行且每行逗号数量一致时认为是csv
+fn looks_like_csv(content: &str) -> bool {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let Some(first) = lines.next() else {
+        return false;
+    };
+    let columns = first.matches(',').count();
+    columns > 0 && lines.all(|line| line.matches(',').count() >= columns)
+}
+
+// 解析输入内容为统一的json值
+fn parse_value(content: &str, format: ConvertFormatType, delimiter: u8) -> Result<Value> {
+    let value = match format {
+        ConvertFormatType::Json => serde_json::from_str(content)?,
+        ConvertFormatType::Yaml => serde_yaml::from_str(content)?,
+        ConvertFormatType::Toml => toml_to_json(toml::from_str(content)?),
+        ConvertFormatType::Csv => {
+            let mut reader = ReaderBuilder::new()
+                .delimiter(delimiter)
+                .from_reader(content.as_bytes());
+            let config = CsvConvertConfig {
+                infer_types: true,
+                ..Default::default()
+            };
+            Value::Array(read_csv_records(&mut reader, &config)?)
+        }
+    };
+    Ok(value)
+}
+
+// toml的日期时间类型转换为字符串，其余类型一一对应
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::Number(i.into()),
+        toml::Value::Float(f) => serde_json::Number::from_f64(f)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(arr) => Value::Array(arr.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(k, v)| (k, toml_to_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+// 检查数据能否用toml表示：顶层必须为表，且不能包含null
+fn check_toml_compatible(value: &Value) -> Result<()> {
+    if !value.is_object() {
+        return Err(anyhow!(
+            "toml顶层必须为表，无法表示{}，请先将数据包装在一个字段中",
+            value_kind(value)
+        ));
+    }
+    match find_null(value, "") {
+        Some(path) => Err(anyhow!("toml不支持null值，位置: {}", path)),
+        None => Ok(()),
+    }
+}
+
+// 查找第一个null值的位置
+fn find_null(value: &Value, path: &str) -> Option<String> {
+    match value {
+        Value::Null => Some(path.to_string()),
+        Value::Array(arr) => arr
+            .iter()
+            .enumerate()
+            .find_map(|(i, v)| find_null(v, &format!("{}[{}]", path, i))),
+        Value::Object(map) => map.iter().find_map(|(k, v)| {
+            let path = if path.is_empty() {
+                k.clone()
+            } else {
+                format!("{}.{}", path, k)
+            };
+            find_null(v, &path)
+        }),
+        _ => None,
+    }
+}
+
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "布尔值",
+        Value::Number(_) => "数字",
+        Value::String(_) => "字符串",
+        Value::Array(_) => "数组",
+        Value::Object(_) => "对象",
+    }
+}
+
+// 取出需要转换为csv的记录列表
+// 支持对象数组、单个对象，以及只有一个数组字段的对象（如toml的 [[players]]）
+fn into_records(value: Value) -> Result<Vec<Map<String, Value>>> {
+    let value = match value {
+        Value::Object(map) if map.len() == 1 && map.values().all(Value::is_array) => {
+            map.into_iter().next().map(|(_, v)| v).unwrap_or_default()
+        }
+        value => value,
+    };
+
+    let rows = match value {
+        Value::Array(rows) => rows,
+        Value::Object(map) => vec![Value::Object(map)],
+        value => {
+            return Err(anyhow!(
+                "csv只能表示对象或对象数组，输入数据为{}",
+                value_kind(&value)
+            ))
+        }
+    };
+
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| match row {
+            Value::Object(map) => Ok(map),
+            row => Err(anyhow!(
+                "csv只能表示对象数组，第{}条记录为{}",
+                i + 1,
+                value_kind(&row)
+            )),
+        })
+        .collect()
+}
+
+// 展开嵌套结构：对象使用 a.b 形式的列名，数组使用 a[0] 形式的列名
+fn flatten_value(prefix: String, value: Value, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (k, v) in map {
+                let key = if prefix.is_empty() {
+                    k
+                } else {
+                    format!("{}.{}", prefix, k)
+                };
+                flatten_value(key, v, out);
+            }
+        }
+        Value::Array(arr) if !arr.is_empty() => {
+            for (i, v) in arr.into_iter().enumerate() {
+                flatten_value(format!("{}[{}]", prefix, i), v, out);
+            }
+        }
+        _ => out.push((prefix, value)),
+    }
+}
+
+fn value_to_field(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+fn value_to_csv(value: Value, delimiter: u8, quote_style: CsvQuoteStyle) -> Result<String> {
+    let records = into_records(value)?;
+
+    // 合并所有记录的字段作为表头，保持字段首次出现的顺序（serde_json开启了preserve_order）
+    let mut headers: Vec<String> = Vec::new();
+    let mut rows = Vec::with_capacity(records.len());
+    for record in records {
+        let mut fields = Vec::new();
+        flatten_value(String::new(), Value::Object(record), &mut fields);
+        for (key, _) in &fields {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+        rows.push(fields.into_iter().collect::<Map<String, Value>>());
+    }
+
+    let mut writer = WriterBuilder::new()
+        .delimiter(delimiter)
+        .quote_style(quote_style.into())
+        .from_writer(Vec::new());
+    writer.write_record(&headers)?;
+    for row in &rows {
+        writer.write_record(
+            headers
+                .iter()
+                .map(|header| row.get(header).map(value_to_field).unwrap_or_default()),
+        )?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+fn convert_data(
+    content: &str,
+    from: ConvertFormatType,
+    to: ConvertFormatType,
+    delimiter: u8,
+    quote_style: CsvQuoteStyle,
+) -> Result<String> {
+    let value = parse_value(content, from, delimiter)
+        .map_err(|e| anyhow!("解析{}内容失败: {}", from, e))?;
+
+    let res_str = match to {
+        ConvertFormatType::Json => serde_json::to_string_pretty(&value)?,
+        ConvertFormatType::Yaml => serde_yaml::to_string(&value)?,
+        ConvertFormatType::Toml => {
+            check_toml_compatible(&value)?;
+            toml::to_string_pretty(&value)?
+        }
+        ConvertFormatType::Csv => value_to_csv(value, delimiter, quote_style)?,
+    };
+
+    Ok(res_str)
+}
+
+// 在json、yaml、toml、csv之间转换文件，未指定输入格式时根据内容自动识别
+pub fn convert_in_file(
+    input_path: &str,
+    save_path: String,
+    from: Option<ConvertFormatType>,
+    to: ConvertFormatType,
+    config: &ConvertConfig,
+) -> Result<()> {
+    // 输入先转码为UTF-8，支持读取gbk、utf-16等编码的文件
+    let mut content = String::new();
+    decode_reader(get_reader_from_path(input_path)?, config.encoding)?
+        .read_to_string(&mut content)?;
+    let from = match from {
+        Some(from) => from,
+        None => detect_format(&content)?,
+    };
+    let res_str = convert_data(&content, from, to, config.delimiter, config.quote_style)?;
+    let mut writer = EncodingWriter::new(get_writer_from_path(&save_path)?, config.output_encoding);
+    writer.write_all(res_str.as_bytes())?;
+    // 输出到标准输出时补充换行，避免与终端提示符连在一起
+    if save_path == "-" && !res_str.ends_with('\n') {
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    // 测试json、yaml、toml转换回csv与原始csv一致
+    #[test]
+    fn test_convert_to_csv_round_trip() -> Result<()> {
+        let expected = fs::read_to_string("./fixtures/process_csv/test.csv")?;
+
+        for (path, from) in [
+            ("./fixtures/process_csv/test.json", ConvertFormatType::Json),
+            ("./fixtures/process_csv/test.yaml", ConvertFormatType::Yaml),
+            ("./fixtures/process_csv/test.toml", ConvertFormatType::Toml),
+        ] {
+            let content = fs::read_to_string(path)?;
+            let result = convert_data(
+                &content,
+                from,
+                ConvertFormatType::Csv,
+                b',',
+                CsvQuoteStyle::Necessary,
+            )?;
+            // 列顺序与原始csv一致
+            assert!(result.lines().eq(expected.lines()), "{}", result);
+        }
+
+        Ok(())
+    }
+
+    // 测试表头合并以及嵌套结构展开
+    #[test]
+    fn test_convert_to_csv_flatten() -> Result<()> {
+        let content = r#"[
+            {"name": "a", "address": {"city": "Turin", "zip": "10100"}, "tags": [1, 2]},
+            {"name": "b", "age": 30, "active": null}
+        ]"#;
+
+        let result = convert_data(
+            content,
+            ConvertFormatType::Json,
+            ConvertFormatType::Csv,
+            b';',
+            CsvQuoteStyle::Always,
+        )?;
+
+        assert_eq!(
+            result,
+            "\"name\";\"address.city\";\"address.zip\";\"tags[0]\";\"tags[1]\";\"age\";\"active\"\n\
+             \"a\";\"Turin\";\"10100\";\"1\";\"2\";\"\";\"\"\n\
+             \"b\";\"\";\"\";\"\";\"\";\"30\";\"\"\n"
+        );
+
+        Ok(())
+    }
+
+    // 测试转换时保持对象字段的原始顺序
+    #[test]
+    fn test_convert_keep_key_order() -> Result<()> {
+        let result = convert_data(
+            r#"{"b": 1, "a": {"z": true, "y": null}}"#,
+            ConvertFormatType::Json,
+            ConvertFormatType::Yaml,
+            b',',
+            CsvQuoteStyle::Necessary,
+        )?;
+        assert_eq!(result, "b: 1\na:\n  z: true\n  y: null\n");
+
+        let result = convert_data(
+            "b = 1\na = 2\n",
+            ConvertFormatType::Toml,
+            ConvertFormatType::Json,
+            b',',
+            CsvQuoteStyle::Necessary,
+        )?;
+        assert_eq!(result, "{\n  \"b\": 1,\n  \"a\": 2\n}");
+        Ok(())
+    }
+
+    // 测试读取gbk编码的csv，并以gbk编码输出
+    #[test]
+    fn test_convert_in_file_encoding() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rrcli_convert_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let input = dir.join("input.csv");
+        let output = dir.join("output.csv");
+        let (content, _, _) = encoding_rs::GBK.encode("姓名,城市\n张三,北京\n");
+        fs::write(&input, content)?;
+
+        let config = ConvertConfig {
+            encoding: Some(CsvEncoding::Gbk),
+            output_encoding: CsvEncoding::Gbk,
+            ..Default::default()
+        };
+        convert_in_file(
+            input.to_str().unwrap(),
+            output.to_string_lossy().into_owned(),
+            Some(ConvertFormatType::Csv),
+            ConvertFormatType::Json,
+            &config,
+        )?;
+        let bytes = fs::read(&output)?;
+        let (result, _, had_errors) = encoding_rs::GBK.decode(&bytes);
+        fs::remove_dir_all(&dir)?;
+
+        assert!(!had_errors);
+        assert!(result.contains("\"姓名\": \"张三\""));
+        assert!(result.contains("\"城市\": \"北京\""));
+        Ok(())
+    }
+
+    // 测试根据内容识别格式
+    #[test]
+    fn test_detect_format() -> Result<()> {
+        for (path, expected) in [
+            ("./fixtures/process_csv/test.json", ConvertFormatType::Json),
+            ("./fixtures/process_csv/test.yaml", ConvertFormatType::Yaml),
+            ("./fixtures/process_csv/test.toml", ConvertFormatType::Toml),
+            ("./fixtures/process_csv/test.csv", ConvertFormatType::Csv),
+        ] {
+            let content = fs::read_to_string(path)?;
+            assert_eq!(detect_format(&content)?, expected);
+        }
+        Ok(())
+    }
+
+    // 测试json、yaml、toml之间互相转换
+    #[test]
+    fn test_convert_data_any_to_any() -> Result<()> {
+        let toml = "[server]\nhost = \"localhost\"\nport = 8080\n";
+        let yaml = convert_data(
+            toml,
+            ConvertFormatType::Toml,
+            ConvertFormatType::Yaml,
+            b',',
+            CsvQuoteStyle::Necessary,
+        )?;
+        assert_eq!(yaml, "server:\n  host: localhost\n  port: 8080\n");
+
+        let json = convert_data(
+            &yaml,
+            ConvertFormatType::Yaml,
+            ConvertFormatType::Json,
+            b',',
+            CsvQuoteStyle::Necessary,
+        )?;
+        let back = convert_data(
+            &json,
+            ConvertFormatType::Json,
+            ConvertFormatType::Toml,
+            b',',
+            CsvQuoteStyle::Necessary,
+        )?;
+        assert_eq!(back, toml);
+        Ok(())
+    }
+
+    // 测试toml无法表示的数据返回明确的错误
+    #[test]
+    fn test_convert_data_toml_errors() {
+        let err = convert_data(
+            "[1, 2]",
+            ConvertFormatType::Json,
+            ConvertFormatType::Toml,
+            b',',
+            CsvQuoteStyle::Necessary,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("toml顶层必须为表"));
+
+        let err = convert_data(
+            r#"{"players": [{"name": "a", "age": null}]}"#,
+            ConvertFormatType::Json,
+            ConvertFormatType::Toml,
+            b',',
+            CsvQuoteStyle::Necessary,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("players[0].age"));
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::CsvFormatType;
+
+use super::{convert_csv_in_file, CsvConvertConfig};
+
+// 批量转换的配置
+#[derive(Debug, Clone)]
+pub struct CsvBatchConfig {
+    // 输出目录，输出文件保留输入相对于匹配根目录的路径
+    pub out_dir: PathBuf,
+    // 出现错误时停止转换尚未开始的文件
+    pub fail_fast: bool,
+    // 并行转换的文件数，0为CPU核数
+    pub jobs: usize,
+    // 使用每个输入的文件名作为toml输出的根表名
+    pub toml_key_from_file: bool,
+}
+
+impl Default for CsvBatchConfig {
+    fn default() -> Self {
+        Self {
+            out_dir: PathBuf::from("."),
+            fail_fast: false,
+            jobs: 0,
+            toml_key_from_file: true,
+        }
+    }
+}
+
+// 批量转换的结果，按输入文件的顺序排列
+#[derive(Debug, Default)]
+pub struct CsvBatchReport {
+    pub succeeded: Vec<(PathBuf, PathBuf)>,
+    pub failed: Vec<(PathBuf, String)>,
+    // 因 fail_fast 未转换的文件数
+    pub skipped: usize,
+}
+
+// 判断输入是否为glob模式，已存在的文件按字面路径处理，如 data[1].csv
+pub fn is_glob_pattern(s: &str) -> bool {
+    has_wildcard(s) && !Path::new(s).is_file()
+}
+
+fn has_wildcard(s: &str) -> bool {
+    s.contains(['*', '?', '['])
+}
+
+// glob模式中第一个包含通配符的部分之前的目录，单个文件时为其所在目录
+fn glob_root(pattern: &str) -> PathBuf {
+    let path = Path::new(pattern);
+    if !is_glob_pattern(pattern) {
+        return path.parent().map(Path::to_path_buf).unwrap_or_default();
+    }
+    path.components()
+        .take_while(|component| !has_wildcard(&component.as_os_str().to_string_lossy()))
+        .collect()
+}
+
+// 输出路径为 输出目录/相对路径，扩展名替换为输出格式
+fn output_path(input: &Path, root: &Path, out_dir: &Path, format_type: CsvFormatType) -> PathBuf {
+    let relative = input.strip_prefix(root).unwrap_or(input);
+    // 去掉 .. 等无法保留在输出目录中的部分
+    let relative: PathBuf = relative
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .collect();
+    out_dir
+        .join(relative)
+        .with_extension(format_type.to_string())
+}
+
+fn convert_one(
+    input: &Path,
+    output: &Path,
+    format_type: CsvFormatType,
+    config: &CsvConvertConfig,
+    batch: &CsvBatchConfig,
+) -> Result<()> {
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut config = config.clone();
+    // 多个文件同时转换时不显示进度
+    config.progress = false;
+    if batch.toml_key_from_file {
+        if let Some(stem) = input.file_stem() {
+            config.toml_key = stem.to_string_lossy().to_string();
+        }
+    }
+    let result = convert_csv_in_file(
+        input.to_string_lossy().to_string(),
+        output.to_string_lossy().to_string(),
+        format_type,
+        &config,
+    );
+    // 转换失败时删除写了一半的输出文件
+    if result.is_err() {
+        let _ = std::fs::remove_file(output);
+    }
+    result
+}
+
+// 列出glob模式匹配的文件，非glob模式时为该文件本身
+pub fn glob_files(pattern: &str) -> Result<Vec<PathBuf>> {
+    if !is_glob_pattern(pattern) {
+        let path = PathBuf::from(pattern);
+        return Ok(if path.is_file() {
+            vec![path]
+        } else {
+            Vec::new()
+        });
+    }
+    Ok(glob::glob(pattern)?
+        .filter_map(|entry| entry.ok())
+        .filter(|path| path.is_file())
+        .collect())
+}
+
+// 转换glob模式（支持 ** 递归匹配）匹配的所有文件，多个文件并行转换
+pub fn convert_csv_batch(
+    pattern: &str,
+    format_type: CsvFormatType,
+    config: &CsvConvertConfig,
+    batch: &CsvBatchConfig,
+) -> Result<CsvBatchReport> {
+    let inputs = glob_files(pattern)?;
+    if inputs.is_empty() {
+        return Err(anyhow!("没有匹配的文件: {}", pattern));
+    }
+    convert_csv_batch_files(pattern, &inputs, format_type, config, batch)
+}
+
+// 转换glob模式匹配到的部分文件，输出路径与转换全部文件时相同
+pub fn convert_csv_batch_files(
+    pattern: &str,
+    inputs: &[PathBuf],
+    format_type: CsvFormatType,
+    config: &CsvConvertConfig,
+    batch: &CsvBatchConfig,
+) -> Result<CsvBatchReport> {
+    let root = glob_root(pattern);
+    let jobs = match batch.jobs {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        jobs => jobs,
+    }
+    .min(inputs.len());
+
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let results = Mutex::new(Vec::with_capacity(inputs.len()));
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(input) = inputs.get(index) else {
+                        break;
+                    };
+                    let output = output_path(input, &root, &batch.out_dir, format_type);
+                    let result = convert_one(input, &output, format_type, config, batch);
+                    if result.is_err() && batch.fail_fast {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                    if let Ok(mut results) = results.lock() {
+                        results.push((index, output, result));
+                    }
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().map_err(|e| anyhow!("{}", e))?;
+    results.sort_by_key(|(index, _, _)| *index);
+    let mut report = CsvBatchReport {
+        skipped: inputs.len() - results.len(),
+        ..Default::default()
+    };
+    for (index, output, result) in results {
+        let input = inputs[index].clone();
+        match result {
+            Ok(()) => report.succeeded.push((input, output)),
+            Err(e) => report.failed.push((input, format!("{:#}", e))),
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_glob_root() {
+        assert_eq!(glob_root("exports/*.csv"), PathBuf::from("exports"));
+        assert_eq!(glob_root("a/b/**/*.csv"), PathBuf::from("a/b"));
+        assert_eq!(glob_root("*.csv"), PathBuf::new());
+        assert_eq!(glob_root("a/b.csv"), PathBuf::from("a"));
+    }
+
+    // 测试文件名中包含通配符字符的已存在文件按字面路径处理
+    #[test]
+    fn test_glob_files_literal_path() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rrcli_literal_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("data[1].csv");
+        fs::write(&path, "id\n1\n")?;
+        let literal = path.to_string_lossy();
+
+        assert!(!is_glob_pattern(&literal));
+        assert_eq!(glob_files(&literal)?, vec![path.clone()]);
+        assert!(is_glob_pattern(&format!("{}/*.csv", dir.display())));
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_csv_batch() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rrcli_batch_{}", std::process::id()));
+        let input_dir = dir.join("exports");
+        fs::create_dir_all(input_dir.join("2024"))?;
+        fs::write(input_dir.join("a.csv"), "id,name\n1,Tom\n")?;
+        fs::write(input_dir.join("2024/b.csv"), "id,name\n2,Jerry\n")?;
+        fs::write(input_dir.join("2024/bad.csv"), "id,name\n3,Spike,extra\n")?;
+
+        let batch = CsvBatchConfig {
+            out_dir: dir.join("converted"),
+            ..Default::default()
+        };
+        let pattern = format!("{}/**/*.csv", input_dir.display());
+        let report = convert_csv_batch(
+            &pattern,
+            CsvFormatType::Json,
+            &CsvConvertConfig::default(),
+            &batch,
+        )?;
+        assert_eq!(report.succeeded.len(), 2);
+        assert_eq!(report.failed.len(), 1);
+        assert!(report.failed[0].0.ends_with("2024/bad.csv"));
+        assert_eq!(
+            fs::read_to_string(dir.join("converted/2024/b.json"))?,
+            "[\n  {\n    \"id\": \"2\",\n    \"name\": \"Jerry\"\n  }\n]"
+        );
+        assert!(dir.join("converted/a.json").exists());
+        assert!(!dir.join("converted/2024/bad.json").exists());
+
+        let pattern = format!("{}/*.txt", input_dir.display());
+        assert!(convert_csv_batch(
+            &pattern,
+            CsvFormatType::Json,
+            &CsvConvertConfig::default(),
+            &batch
+        )
+        .is_err());
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
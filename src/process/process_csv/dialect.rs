@@ -0,0 +1,216 @@
+use std::io::{BufReader, Cursor, Read, Write};
+
+use anyhow::Result;
+use csv::{Reader, ReaderBuilder, StringRecord, Trim, Writer, WriterBuilder};
+
+use crate::CsvEncoding;
+
+use super::encoding::{decode_reader, EncodingWriter};
+
+// 自动识别时的候选分隔符
+const SNIFF_DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+// 自动识别时最多检查的行数
+const SNIFF_LINES: usize = 20;
+// 自动识别时读取的最大字节数
+const SNIFF_BYTES: u64 = 16 * 1024;
+
+// csv方言配置
+#[derive(Debug, Clone)]
+pub struct CsvDialect {
+    // 分隔符，未指定时根据文件内容自动识别
+    pub delimiter: Option<u8>,
+    pub quote: u8,
+    // 转义字符，如 \，默认只识别双写引号
+    pub escape: Option<u8>,
+    // 是否包含表头，不包含时生成 col1..colN 作为列名
+    pub has_headers: bool,
+    // 注释行的起始字符，如 #
+    pub comment: Option<u8>,
+    // 是否允许每行字段数不一致
+    pub flexible: bool,
+    // 是否去除字段首尾的空白
+    pub trim: bool,
+    // 输入的编码，未指定时根据BOM及内容自动识别
+    pub encoding: Option<CsvEncoding>,
+    // 输出的编码，如输出gbk供Excel打开
+    pub output_encoding: CsvEncoding,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            quote: b'"',
+            escape: None,
+            has_headers: true,
+            comment: None,
+            flexible: false,
+            trim: false,
+            encoding: None,
+            output_encoding: CsvEncoding::Utf8,
+        }
+    }
+}
+
+impl CsvDialect {
+    pub fn builder(&self, delimiter: u8) -> ReaderBuilder {
+        let mut builder = ReaderBuilder::new();
+        builder
+            .delimiter(delimiter)
+            .quote(self.quote)
+            .escape(self.escape)
+            .has_headers(self.has_headers)
+            .comment(self.comment)
+            .flexible(self.flexible)
+            .trim(if self.trim { Trim::All } else { Trim::None });
+        builder
+    }
+
+    fn writer_builder(&self, delimiter: u8) -> WriterBuilder {
+        let mut builder = WriterBuilder::new();
+        builder
+            .delimiter(delimiter)
+            .quote(self.quote)
+            .flexible(self.flexible);
+        builder
+    }
+}
+
+// 按方言配置创建csv读取器，输入先转码为UTF-8，未指定分隔符时读取开头的内容识别分隔符
+pub fn build_reader<R: Read>(input: R, dialect: &CsvDialect) -> Result<Reader<impl Read>> {
+    Ok(build_reader_with_delimiter(input, dialect)?.0)
+}
+
+// 同 build_reader，同时返回使用的分隔符，供输出csv时保持与输入一致
+pub fn build_reader_with_delimiter<R: Read>(
+    input: R,
+    dialect: &CsvDialect,
+) -> Result<(Reader<impl Read>, u8)> {
+    let mut input = decode_reader(input, dialect.encoding)?;
+    // 解码器每次读取的长度不定，识别分隔符时先读取足够的样本，再与剩余内容拼接
+    let mut sample = Vec::new();
+    let delimiter = match dialect.delimiter {
+        Some(delimiter) => delimiter,
+        None => {
+            input.by_ref().take(SNIFF_BYTES).read_to_end(&mut sample)?;
+            sniff_delimiter(&sample, dialect)
+        }
+    };
+    let input = BufReader::with_capacity(64 * 1024, Cursor::new(sample).chain(input));
+    Ok((dialect.builder(delimiter).from_reader(input), delimiter))
+}
+
+// 按方言配置创建csv写出器：分隔符与输入一致，使用相同的引号，允许不规则行时输出也允许，
+// 输出内容转码为指定的编码
+pub fn build_writer<W: Write>(
+    output: W,
+    dialect: &CsvDialect,
+    delimiter: u8,
+) -> Writer<EncodingWriter<W>> {
+    dialect
+        .writer_builder(delimiter)
+        .from_writer(EncodingWriter::new(output, dialect.output_encoding))
+}
+
+// 追加写入已有内容的输出，不再写入BOM
+pub fn build_append_writer<W: Write>(
+    output: W,
+    dialect: &CsvDialect,
+    delimiter: u8,
+) -> Writer<EncodingWriter<W>> {
+    dialect
+        .writer_builder(delimiter)
+        .from_writer(EncodingWriter::appending(output, dialect.output_encoding))
+}
+
+// 读取表头，无表头时根据第一行的字段数生成 col1..colN
+pub fn read_headers<R: Read>(reader: &mut Reader<R>, dialect: &CsvDialect) -> Result<StringRecord> {
+    let headers = reader.headers()?;
+    if dialect.has_headers {
+        Ok(headers.clone())
+    } else {
+        Ok((1..=headers.len()).map(column_name).collect())
+    }
+}
+
+// 生成的列名，从1开始
+pub fn column_name(index: usize) -> String {
+    format!("col{}", index)
+}
+
+// 统计候选分隔符在前几行中（引号外）出现的次数，选择每行次数一致且最多的分隔符
+pub fn sniff_delimiter(sample: &[u8], dialect: &CsvDialect) -> u8 {
+    let sample = String::from_utf8_lossy(sample);
+    let mut lines: Vec<&str> = sample
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter(|line| {
+            dialect
+                .comment
+                .is_none_or(|comment| !line.as_bytes().starts_with(&[comment]))
+        })
+        .take(SNIFF_LINES)
+        .collect();
+    // 样本末尾的行可能不完整
+    if lines.len() > 1 && !sample.ends_with('\n') {
+        lines.pop();
+    }
+
+    SNIFF_DELIMITERS
+        .iter()
+        .filter_map(|&delimiter| {
+            let counts: Vec<usize> = lines
+                .iter()
+                .map(|line| count_unquoted(line, delimiter, dialect.quote))
+                .collect();
+            let first = *counts.first()?;
+            let consistent = counts.iter().filter(|&&count| count == first).count();
+            (first > 0).then_some((delimiter, consistent, first))
+        })
+        .max_by_key(|&(_, consistent, count)| (consistent, count))
+        .map(|(delimiter, _, _)| delimiter)
+        .unwrap_or(b',')
+}
+
+fn count_unquoted(line: &str, delimiter: u8, quote: u8) -> usize {
+    let mut quoted = false;
+    line.bytes()
+        .filter(|&b| {
+            if b == quote {
+                quoted = !quoted;
+            }
+            b == delimiter && !quoted
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sniff_delimiter() {
+        let dialect = CsvDialect::default();
+        assert_eq!(sniff_delimiter(b"a,b,c\n1,2,3\n", &dialect), b',');
+        assert_eq!(sniff_delimiter(b"a\tb\tc\n1\t2,5\t3\n", &dialect), b'\t');
+        assert_eq!(
+            sniff_delimiter(b"name;price\n\"Rossi, Paolo\";1,5\n", &dialect),
+            b';'
+        );
+        assert_eq!(sniff_delimiter(b"single\nvalue\n", &dialect), b',');
+    }
+
+    #[test]
+    fn test_read_headers_without_header() -> Result<()> {
+        let dialect = CsvDialect {
+            has_headers: false,
+            ..Default::default()
+        };
+        let mut reader = build_reader("1|2|3\n4|5|6\n".as_bytes(), &dialect)?;
+        let headers = read_headers(&mut reader, &dialect)?;
+
+        assert_eq!(headers, vec!["col1", "col2", "col3"]);
+        assert_eq!(reader.records().count(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,516 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+use anyhow::{anyhow, Result};
+use csv::StringRecord;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    utils::{get_reader_from_path, get_writer_from_path},
+    CsvDiffFormat,
+};
+
+use super::{
+    dialect::{build_reader, read_headers},
+    filter::value_text,
+    record_to_map, CsvConvertConfig,
+};
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl From<ChangeKind> for &'static str {
+    fn from(value: ChangeKind) -> Self {
+        match value {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Modified => "modified",
+        }
+    }
+}
+
+// 单元格的变化
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellChange {
+    pub column: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+// 一行的变化，row为新增、修改后的记录或被删除的记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowChange {
+    pub kind: ChangeKind,
+    pub key: Vec<String>,
+    pub row: Map<String, Value>,
+    pub cells: Vec<CellChange>,
+}
+
+// 两个csv文件的差异，只比较两个文件共有的列
+#[derive(Debug, Default)]
+pub struct CsvDiff {
+    pub key_columns: Vec<String>,
+    // 新文件的列，其后为只在旧文件中存在的列
+    pub columns: Vec<String>,
+    pub added_columns: Vec<String>,
+    pub removed_columns: Vec<String>,
+    pub changes: Vec<RowChange>,
+}
+
+// 以键列的值为索引读取全部记录，键重复时报错
+struct KeyedRecords {
+    headers: StringRecord,
+    keys: Vec<Vec<String>>,
+    rows: HashMap<Vec<String>, Map<String, Value>>,
+}
+
+fn read_keyed<R: Read>(
+    input: R,
+    key_columns: &[String],
+    config: &CsvConvertConfig,
+) -> Result<KeyedRecords> {
+    let mut reader = build_reader(input, &config.dialect)?;
+    let headers = read_headers(&mut reader, &config.dialect)?;
+    for column in key_columns {
+        if !headers.iter().any(|header| header == column) {
+            return Err(anyhow!("键列不存在: {}", column));
+        }
+    }
+
+    let mut keys = Vec::new();
+    let mut rows = HashMap::new();
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        let row = record_to_map(&headers, &record, config)?;
+        let key: Vec<String> = key_columns
+            .iter()
+            .map(|column| row.get(column).map(value_text).unwrap_or_default())
+            .collect();
+        if rows.insert(key.clone(), row).is_some() {
+            return Err(anyhow!("第{}行的键重复: {}", line, key.join(",")));
+        }
+        keys.push(key);
+    }
+    Ok(KeyedRecords {
+        headers,
+        keys,
+        rows,
+    })
+}
+
+// 按键列比较两个csv，列的顺序不影响结果
+// 删除和修改的行按旧文件中的顺序输出，新增的行按新文件中的顺序排在最后
+pub fn diff_csv<R1: Read, R2: Read>(
+    old: R1,
+    new: R2,
+    key_columns: &[String],
+    config: &CsvConvertConfig,
+) -> Result<CsvDiff> {
+    if key_columns.is_empty() {
+        return Err(anyhow!("至少需要指定一个键列"));
+    }
+    let old = read_keyed(old, key_columns, config)?;
+    let mut new = read_keyed(new, key_columns, config)?;
+
+    let in_headers = |headers: &StringRecord, column: &str| headers.iter().any(|h| h == column);
+    let added_columns: Vec<String> = new
+        .headers
+        .iter()
+        .filter(|column| !in_headers(&old.headers, column))
+        .map(String::from)
+        .collect();
+    let removed_columns: Vec<String> = old
+        .headers
+        .iter()
+        .filter(|column| !in_headers(&new.headers, column))
+        .map(String::from)
+        .collect();
+    let common: Vec<&str> = new
+        .headers
+        .iter()
+        .filter(|column| in_headers(&old.headers, column))
+        .collect();
+
+    let mut changes = Vec::new();
+    let KeyedRecords {
+        keys: old_keys,
+        rows: mut old_rows,
+        ..
+    } = old;
+    for key in old_keys {
+        let old_row = old_rows.remove(&key).unwrap_or_default();
+        match new.rows.remove(&key) {
+            None => changes.push(RowChange {
+                kind: ChangeKind::Removed,
+                key,
+                row: old_row,
+                cells: Vec::new(),
+            }),
+            Some(new_row) => {
+                let cells: Vec<CellChange> = common
+                    .iter()
+                    .filter_map(|column| {
+                        let old = old_row.get(*column).cloned().unwrap_or_default();
+                        let new = new_row.get(*column).cloned().unwrap_or_default();
+                        (old != new).then(|| CellChange {
+                            column: column.to_string(),
+                            old,
+                            new,
+                        })
+                    })
+                    .collect();
+                if !cells.is_empty() {
+                    changes.push(RowChange {
+                        kind: ChangeKind::Modified,
+                        key,
+                        row: new_row,
+                        cells,
+                    });
+                }
+            }
+        }
+    }
+    for key in new.keys {
+        if let Some(row) = new.rows.remove(&key) {
+            changes.push(RowChange {
+                kind: ChangeKind::Added,
+                key,
+                row,
+                cells: Vec::new(),
+            });
+        }
+    }
+
+    let columns = new
+        .headers
+        .iter()
+        .map(String::from)
+        .chain(removed_columns.iter().cloned())
+        .collect();
+    Ok(CsvDiff {
+        key_columns: key_columns.to_vec(),
+        columns,
+        added_columns,
+        removed_columns,
+        changes,
+    })
+}
+
+// json pointer中的 ~ 和 / 需要转义
+fn escape_pointer(s: &str) -> String {
+    s.replace('~', "~0").replace('/', "~1")
+}
+
+impl CsvDiff {
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.changes
+            .iter()
+            .filter(|change| change.kind == kind)
+            .count()
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "新增 {} 行，删除 {} 行，修改 {} 行",
+            self.count(ChangeKind::Added),
+            self.count(ChangeKind::Removed),
+            self.count(ChangeKind::Modified)
+        )
+    }
+
+    fn format_key(&self, key: &[String]) -> String {
+        self.key_columns
+            .iter()
+            .zip(key)
+            .map(|(column, value)| format!("{}={}", column, value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    // 输出为终端文本，color为true时使用颜色区分新增、删除和修改
+    pub fn write_text(&self, writer: &mut impl Write, color: bool) -> Result<()> {
+        let paint = |code: &'static str| if color { code } else { "" };
+        for column in &self.added_columns {
+            writeln!(writer, "{}+ 列 {}{}", paint(GREEN), column, paint(RESET))?;
+        }
+        for column in &self.removed_columns {
+            writeln!(writer, "{}- 列 {}{}", paint(RED), column, paint(RESET))?;
+        }
+
+        for change in &self.changes {
+            let key = self.format_key(&change.key);
+            match change.kind {
+                ChangeKind::Added | ChangeKind::Removed => {
+                    let (sign, code) = if change.kind == ChangeKind::Added {
+                        ("+", GREEN)
+                    } else {
+                        ("-", RED)
+                    };
+                    let row: Vec<String> = self
+                        .columns
+                        .iter()
+                        .filter_map(|column| {
+                            let value = change.row.get(column)?;
+                            Some(format!("{}={}", column, value_text(value)))
+                        })
+                        .collect();
+                    writeln!(
+                        writer,
+                        "{}{} [{}] {}{}",
+                        paint(code),
+                        sign,
+                        key,
+                        row.join(", "),
+                        paint(RESET)
+                    )?;
+                }
+                ChangeKind::Modified => {
+                    writeln!(writer, "{}~ [{}]{}", paint(YELLOW), key, paint(RESET))?;
+                    for cell in &change.cells {
+                        writeln!(
+                            writer,
+                            "    {}: {}{}{} -> {}{}{}",
+                            cell.column,
+                            paint(RED),
+                            value_text(&cell.old),
+                            paint(RESET),
+                            paint(GREEN),
+                            value_text(&cell.new),
+                            paint(RESET)
+                        )?;
+                    }
+                }
+            }
+        }
+        writeln!(writer, "{}", self.summary())?;
+        Ok(())
+    }
+
+    // 参照JSON Patch输出操作列表，path为 /键 或 /键/列名，修改时附带原值
+    // 多个键列时每个键列的值各占一段，如 /1/2/列名，避免值中的分隔符造成歧义
+    pub fn to_json_patch(&self) -> Value {
+        let mut ops = Vec::new();
+        for change in &self.changes {
+            let path: String = change
+                .key
+                .iter()
+                .map(|value| format!("/{}", escape_pointer(value)))
+                .collect();
+            match change.kind {
+                ChangeKind::Added => ops.push(json!({
+                    "op": "add",
+                    "path": path,
+                    "value": change.row,
+                })),
+                ChangeKind::Removed => ops.push(json!({
+                    "op": "remove",
+                    "path": path,
+                    "old": change.row,
+                })),
+                ChangeKind::Modified => {
+                    for cell in &change.cells {
+                        ops.push(json!({
+                            "op": "replace",
+                            "path": format!("{}/{}", path, escape_pointer(&cell.column)),
+                            "value": cell.new,
+                            "old": cell.old,
+                        }));
+                    }
+                }
+            }
+        }
+        Value::Array(ops)
+    }
+
+    // 输出为csv，首列为变化类型，修改的行输出修改后的值，并在changed_columns列中列出变化的列
+    pub fn write_csv(&self, writer: impl Write) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(
+            ["change_type"]
+                .into_iter()
+                .chain(self.columns.iter().map(String::as_str))
+                .chain(["changed_columns"]),
+        )?;
+        for change in &self.changes {
+            let changed: Vec<&str> = change.cells.iter().map(|c| c.column.as_str()).collect();
+            let values = self
+                .columns
+                .iter()
+                .map(|column| change.row.get(column).map(value_text).unwrap_or_default());
+            writer.write_record(
+                [Into::<&'static str>::into(change.kind).to_string()]
+                    .into_iter()
+                    .chain(values)
+                    .chain([changed.join(";")]),
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+// 比较两个csv文件并按指定格式写出差异，返回差异的行数
+pub fn diff_csv_in_file(
+    old_path: &str,
+    new_path: &str,
+    save_path: &str,
+    key_columns: &[String],
+    format: CsvDiffFormat,
+    config: &CsvConvertConfig,
+    color: bool,
+) -> Result<usize> {
+    let diff = diff_csv(
+        get_reader_from_path(old_path)?,
+        get_reader_from_path(new_path)?,
+        key_columns,
+        config,
+    )?;
+    if format != CsvDiffFormat::Text
+        && !(diff.added_columns.is_empty() && diff.removed_columns.is_empty())
+    {
+        eprintln!(
+            "列变化: 新增 [{}]，删除 [{}]",
+            diff.added_columns.join(", "),
+            diff.removed_columns.join(", ")
+        );
+    }
+
+    let mut output = get_writer_from_path(save_path)?;
+    match format {
+        CsvDiffFormat::Text => diff.write_text(&mut output, color)?,
+        CsvDiffFormat::Json => {
+            serde_json::to_writer_pretty(&mut output, &diff.to_json_patch())?;
+            writeln!(output)?;
+        }
+        CsvDiffFormat::Csv => diff.write_csv(&mut output)?,
+    }
+    output.flush()?;
+    Ok(diff.changes.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const OLD: &str = "id,name,age,team\n1,Tom,30,A\n2,Jerry,5,B\n3,Spike,7,A\n";
+    // 列顺序不同，删除了team列，新增了city列
+    const NEW: &str = "age,id,name,city\n31,1,Tom,Turin\n7,3,Spike,Milan\n2,4,Tyke,Rome\n";
+
+    fn diff() -> Result<CsvDiff> {
+        diff_csv(
+            OLD.as_bytes(),
+            NEW.as_bytes(),
+            &["id".to_string()],
+            &CsvConvertConfig::default(),
+        )
+    }
+
+    #[test]
+    fn test_diff_csv() -> Result<()> {
+        let diff = diff()?;
+        assert_eq!(diff.added_columns, vec!["city"]);
+        assert_eq!(diff.removed_columns, vec!["team"]);
+
+        let summary: Vec<(ChangeKind, Vec<String>)> = diff
+            .changes
+            .iter()
+            .map(|change| (change.kind, change.key.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Modified, vec!["1".to_string()]),
+                (ChangeKind::Removed, vec!["2".to_string()]),
+                (ChangeKind::Added, vec!["4".to_string()]),
+            ]
+        );
+        assert_eq!(
+            diff.changes[0].cells,
+            vec![CellChange {
+                column: "age".to_string(),
+                old: json!("30"),
+                new: json!("31"),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_csv_output() -> Result<()> {
+        let diff = diff()?;
+
+        let mut text = Vec::new();
+        diff.write_text(&mut text, false)?;
+        assert_eq!(
+            String::from_utf8(text)?,
+            "+ 列 city\n\
+             - 列 team\n\
+             ~ [id=1]\n    age: 30 -> 31\n\
+             - [id=2] age=5, id=2, name=Jerry, team=B\n\
+             + [id=4] age=2, id=4, name=Tyke, city=Rome\n\
+             新增 1 行，删除 1 行，修改 1 行\n"
+        );
+
+        assert_eq!(
+            diff.to_json_patch()[0],
+            json!({"op": "replace", "path": "/1/age", "value": "31", "old": "30"})
+        );
+        assert_eq!(diff.to_json_patch()[2]["op"], json!("add"));
+
+        let mut csv = Vec::new();
+        diff.write_csv(&mut csv)?;
+        assert_eq!(
+            String::from_utf8(csv)?,
+            "change_type,age,id,name,city,team,changed_columns\n\
+             modified,31,1,Tom,Turin,,age\n\
+             removed,5,2,Jerry,,B,\n\
+             added,2,4,Tyke,Rome,,\n"
+        );
+        Ok(())
+    }
+
+    // 测试多个键列时json的path中每个键各占一段
+    #[test]
+    fn test_diff_csv_json_patch_multi_key() -> Result<()> {
+        let old = "a,b,v\n\"1,2\",3,x\n1,\"2,3\",y\n";
+        let new = "a,b,v\n\"1,2\",3,x2\n1,\"2,3\",y2\n";
+        let diff = diff_csv(
+            old.as_bytes(),
+            new.as_bytes(),
+            &["a".to_string(), "b".to_string()],
+            &CsvConvertConfig::default(),
+        )?;
+        let paths: Vec<Value> = diff
+            .to_json_patch()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|op| op["path"].clone())
+            .collect();
+        assert_eq!(paths, vec![json!("/1,2/3/v"), json!("/1/2,3/v")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_csv_errors() {
+        let config = CsvConvertConfig::default();
+        let key = ["missing".to_string()];
+        assert!(diff_csv(OLD.as_bytes(), NEW.as_bytes(), &key, &config).is_err());
+
+        let duplicated = "id,name\n1,a\n1,b\n";
+        let key = ["id".to_string()];
+        assert!(diff_csv(duplicated.as_bytes(), NEW.as_bytes(), &key, &config).is_err());
+    }
+}
@@ -0,0 +1,344 @@
+use std::io::{self, Chain, Cursor, Read, Write};
+
+use anyhow::Result;
+use encoding_rs::{Encoding, GB18030, GBK, UTF_16BE, UTF_16LE};
+use encoding_rs_io::{DecodeReaderBytes, DecodeReaderBytesBuilder};
+
+use crate::CsvEncoding;
+
+const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+const UTF16LE_BOM: [u8; 2] = [0xff, 0xfe];
+const UTF16BE_BOM: [u8; 2] = [0xfe, 0xff];
+// 自动识别编码时读取的样本大小
+const SAMPLE_BYTES: u64 = 64 * 1024;
+// 检查UTF-8时每次读取的字节数
+const CHUNK_BYTES: usize = 8 * 1024;
+
+// 根据BOM及内容识别编码：无BOM时，包含0字节视为UTF-16，合法的UTF-8视为UTF-8，否则视为GB18030（兼容GBK）
+pub fn detect_encoding(sample: &[u8]) -> CsvEncoding {
+    if sample.starts_with(&UTF8_BOM) {
+        return CsvEncoding::Utf8Bom;
+    }
+    if sample.starts_with(&UTF16LE_BOM) {
+        return CsvEncoding::Utf16Le;
+    }
+    if sample.starts_with(&UTF16BE_BOM) {
+        return CsvEncoding::Utf16Be;
+    }
+
+    // 文本文件的UTF-8及GBK编码中不会出现0字节，UTF-16中ASCII字符的高位为0，LE时在奇数位，BE时在偶数位
+    if sample.contains(&0) {
+        let even = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+        let odd = sample
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count();
+        return if odd >= even {
+            CsvEncoding::Utf16Le
+        } else {
+            CsvEncoding::Utf16Be
+        };
+    }
+
+    match std::str::from_utf8(sample) {
+        Ok(_) => CsvEncoding::Utf8,
+        // 样本末尾可能截断了多字节字符
+        Err(e) if e.error_len().is_none() => CsvEncoding::Utf8,
+        Err(_) => CsvEncoding::Gb18030,
+    }
+}
+
+// UTF-8不需要转码，BOM由解码器去除
+fn decoder_encoding(encoding: CsvEncoding) -> Option<&'static Encoding> {
+    match encoding {
+        CsvEncoding::Utf8 | CsvEncoding::Utf8Bom => None,
+        CsvEncoding::Utf16Le => Some(UTF_16LE),
+        CsvEncoding::Utf16Be => Some(UTF_16BE),
+        CsvEncoding::Gbk => Some(GBK),
+        CsvEncoding::Gb18030 => Some(GB18030),
+    }
+}
+
+// 将输入转码为UTF-8，未指定编码时读取开头的样本自动识别
+// 自动识别为UTF-8的内容在样本之后出现不合法的UTF-8时，剩余内容改按GB18030解码
+pub fn decode_reader<R: Read>(
+    mut input: R,
+    encoding: Option<CsvEncoding>,
+) -> Result<DecodeReader<R>> {
+    let mut sample = Vec::with_capacity(SAMPLE_BYTES as usize);
+    input.by_ref().take(SAMPLE_BYTES).read_to_end(&mut sample)?;
+    let detected = match encoding {
+        Some(encoding) => encoding,
+        None => detect_encoding(&sample),
+    };
+    let input = Cursor::new(sample).chain(input);
+    if encoding.is_none() && detected == CsvEncoding::Utf8 {
+        return Ok(DecodeReader {
+            decoder: None,
+            utf8: Some(input),
+            pending: Vec::new(),
+            output: Vec::new(),
+            pos: 0,
+        });
+    }
+    Ok(DecodeReader {
+        decoder: Some(build_decoder(detected, Vec::new(), input)),
+        utf8: None,
+        pending: Vec::new(),
+        output: Vec::new(),
+        pos: 0,
+    })
+}
+
+type Sampled<R> = Chain<Cursor<Vec<u8>>, R>;
+
+fn build_decoder<R: Read>(
+    encoding: CsvEncoding,
+    head: Vec<u8>,
+    input: Sampled<R>,
+) -> DecodeReaderBytes<Sampled<Sampled<R>>, Vec<u8>> {
+    DecodeReaderBytesBuilder::new()
+        .encoding(decoder_encoding(encoding))
+        .bom_sniffing(true)
+        .build(Cursor::new(head).chain(input))
+}
+
+// 转码为UTF-8的读取器
+pub struct DecodeReader<R: Read> {
+    decoder: Option<DecodeReaderBytes<Sampled<Sampled<R>>, Vec<u8>>>,
+    // 自动识别为UTF-8时直接读取并检查内容
+    utf8: Option<Sampled<R>>,
+    // 上次读取末尾被截断的多字节字符
+    pending: Vec<u8>,
+    // 已确认为合法UTF-8、等待输出的内容
+    output: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> DecodeReader<R> {
+    // 返回开头合法的UTF-8的长度，末尾被截断的字符留待后续内容，遇到不合法的内容时改用GB18030解码剩余内容
+    fn check_utf8(&mut self, bytes: &[u8], eof: bool) -> usize {
+        let (valid, invalid) = match std::str::from_utf8(bytes) {
+            Ok(s) => (s.len(), false),
+            // 已到结尾时被截断的字符视为不合法
+            Err(e) if e.error_len().is_none() && !eof => (e.valid_up_to(), false),
+            Err(e) => (e.valid_up_to(), true),
+        };
+        if invalid {
+            if let Some(input) = self.utf8.take() {
+                let rest = bytes[valid..].to_vec();
+                self.decoder = Some(build_decoder(CsvEncoding::Gb18030, rest, input));
+            }
+        } else {
+            self.pending = bytes[valid..].to_vec();
+        }
+        valid
+    }
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.pos < self.output.len() {
+                let n = buf.len().min(self.output.len() - self.pos);
+                buf[..n].copy_from_slice(&self.output[self.pos..self.pos + n]);
+                self.pos += n;
+                return Ok(n);
+            }
+            if let Some(decoder) = &mut self.decoder {
+                return decoder.read(buf);
+            }
+            let Some(input) = &mut self.utf8 else {
+                return Ok(0);
+            };
+            if self.pending.is_empty() {
+                // 没有截断的字符时直接读取到调用方的缓冲区
+                let n = input.read(buf)?;
+                if n == 0 {
+                    return Ok(0);
+                }
+                let valid = self.check_utf8(&buf[..n], false);
+                if valid > 0 {
+                    return Ok(valid);
+                }
+            } else {
+                let mut chunk = [0; CHUNK_BYTES];
+                let n = input.read(&mut chunk)?;
+                let mut bytes = std::mem::take(&mut self.pending);
+                bytes.extend_from_slice(&chunk[..n]);
+                let valid = self.check_utf8(&bytes, n == 0);
+                bytes.truncate(valid);
+                self.output = bytes;
+                self.pos = 0;
+            }
+        }
+    }
+}
+
+// 将写入的UTF-8内容转码为指定编码后写出，UTF-16输出时写入BOM以便Excel识别
+pub struct EncodingWriter<W: Write> {
+    writer: W,
+    encoding: CsvEncoding,
+    // 被截断的多字节字符，等待后续内容
+    pending: Vec<u8>,
+    started: bool,
+}
+
+impl<W: Write> EncodingWriter<W> {
+    pub fn new(writer: W, encoding: CsvEncoding) -> Self {
+        Self {
+            writer,
+            encoding,
+            pending: Vec::new(),
+            started: false,
+        }
+    }
+
+    // 追加写入已有内容的输出，不再写入BOM
+    pub fn appending(writer: W, encoding: CsvEncoding) -> Self {
+        Self {
+            started: true,
+            ..Self::new(writer, encoding)
+        }
+    }
+
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        if !self.started {
+            self.started = true;
+            match self.encoding {
+                CsvEncoding::Utf8Bom => self.writer.write_all(&UTF8_BOM)?,
+                CsvEncoding::Utf16Le => self.writer.write_all(&UTF16LE_BOM)?,
+                CsvEncoding::Utf16Be => self.writer.write_all(&UTF16BE_BOM)?,
+                _ => {}
+            }
+        }
+        match self.encoding {
+            CsvEncoding::Utf8 | CsvEncoding::Utf8Bom => self.writer.write_all(s.as_bytes()),
+            CsvEncoding::Utf16Le => {
+                let bytes: Vec<u8> = s.encode_utf16().flat_map(u16::to_le_bytes).collect();
+                self.writer.write_all(&bytes)
+            }
+            CsvEncoding::Utf16Be => {
+                let bytes: Vec<u8> = s.encode_utf16().flat_map(u16::to_be_bytes).collect();
+                self.writer.write_all(&bytes)
+            }
+            CsvEncoding::Gbk | CsvEncoding::Gb18030 => {
+                let encoding = if self.encoding == CsvEncoding::Gbk {
+                    GBK
+                } else {
+                    GB18030
+                };
+                let (bytes, _, unmappable) = encoding.encode(s);
+                if unmappable {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("内容中存在无法使用 {} 编码的字符", self.encoding),
+                    ));
+                }
+                self.writer.write_all(&bytes)
+            }
+        }
+    }
+}
+
+impl<W: Write> Write for EncodingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // UTF-8无需转码，直接写出以支持二进制格式
+        if self.encoding == CsvEncoding::Utf8 {
+            return self.writer.write(buf);
+        }
+        self.pending.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(s) => s.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+        let pending = std::mem::take(&mut self.pending);
+        // valid之前的内容已确认为合法的UTF-8
+        let s = std::str::from_utf8(&pending[..valid]).unwrap_or_default();
+        self.write_str(s)?;
+        self.pending = pending[valid..].to_vec();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "输出内容以不完整的UTF-8字符结尾",
+            ));
+        }
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decode(input: &[u8], encoding: Option<CsvEncoding>) -> Result<String> {
+        let mut output = String::new();
+        decode_reader(input, encoding)?.read_to_string(&mut output)?;
+        Ok(output)
+    }
+
+    fn encode(input: &str, encoding: CsvEncoding) -> Result<Vec<u8>> {
+        let mut writer = EncodingWriter::new(Vec::new(), encoding);
+        // 逐字节写入，检查被截断的多字节字符
+        for b in input.as_bytes() {
+            writer.write_all(&[*b])?;
+        }
+        writer.flush()?;
+        Ok(writer.writer)
+    }
+
+    #[test]
+    fn test_detect_encoding() {
+        let text = "姓名,城市\n张三,北京\n";
+        let (gbk, _, _) = GBK.encode(text);
+        let utf16: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+
+        assert_eq!(detect_encoding(text.as_bytes()), CsvEncoding::Utf8);
+        assert_eq!(detect_encoding(b"\xef\xbb\xbfa,b\n"), CsvEncoding::Utf8Bom);
+        assert_eq!(detect_encoding(b"\xff\xfea\x00"), CsvEncoding::Utf16Le);
+        assert_eq!(detect_encoding(&gbk), CsvEncoding::Gb18030);
+        assert_eq!(detect_encoding(&utf16), CsvEncoding::Utf16Le);
+        assert_eq!(detect_encoding(&text.as_bytes()[..4]), CsvEncoding::Utf8);
+    }
+
+    #[test]
+    fn test_encoding_round_trip() -> Result<()> {
+        let text = "姓名,城市\n张三,北京\n";
+        for encoding in [
+            CsvEncoding::Utf8,
+            CsvEncoding::Utf8Bom,
+            CsvEncoding::Utf16Le,
+            CsvEncoding::Utf16Be,
+            CsvEncoding::Gbk,
+            CsvEncoding::Gb18030,
+        ] {
+            let encoded = encode(text, encoding)?;
+            assert_eq!(decode(&encoded, Some(encoding))?, text, "{}", encoding);
+            assert_eq!(decode(&encoded, None)?, text, "{}", encoding);
+        }
+        assert!(encode("😀", CsvEncoding::Gbk).is_err());
+        Ok(())
+    }
+
+    // 测试开头为ASCII的gbk内容：样本之内或之后出现中文时都能正确解码
+    #[test]
+    fn test_decode_gbk_after_ascii() -> Result<()> {
+        for prefix_rows in [1_000, 10_000] {
+            let mut text = "id,name\n".to_string();
+            for i in 0..prefix_rows {
+                text.push_str(&format!("{},Tom\n", i));
+            }
+            text.push_str("0,张三\n");
+            let (gbk, _, _) = GBK.encode(&text);
+            assert_eq!(decode(&gbk, None)?, text, "{}", prefix_rows);
+        }
+        Ok(())
+    }
+}
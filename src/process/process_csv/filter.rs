@@ -0,0 +1,444 @@
+use std::cmp::Ordering;
+
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+
+// 过滤表达式，如 age>30 && (nationality==Italy || `Kit Number` <= 10)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CompareOp, Operand),
+    Truthy(Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    // 列名，出现在比较右侧且列不存在时按字符串处理，如 nationality==Italy 中的 Italy
+    Ident(String),
+    // 反引号包裹的列名，如 `Kit Number`
+    Column(String),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Column(String),
+    Literal(Value),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                i += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                i += 2;
+            }
+            '=' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Eq));
+                i += 2;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Ne));
+                i += 2;
+            }
+            '~' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Contains));
+                i += 2;
+            }
+            '>' | '<' => {
+                let eq = next == Some('=');
+                let op = match (c, eq) {
+                    ('>', false) => CompareOp::Gt,
+                    ('>', true) => CompareOp::Ge,
+                    ('<', false) => CompareOp::Lt,
+                    _ => CompareOp::Le,
+                };
+                tokens.push(Token::Op(op));
+                i += if eq { 2 } else { 1 };
+            }
+            '!' => {
+                tokens.push(Token::Not);
+                i += 1;
+            }
+            '\'' | '"' | '`' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&ch| ch == c)
+                    .ok_or_else(|| anyhow!("表达式中的引号没有闭合: {}", input))?;
+                let text: String = chars[i + 1..i + 1 + end].iter().collect();
+                tokens.push(if c == '`' {
+                    Token::Column(text)
+                } else {
+                    Token::Literal(Value::String(text))
+                });
+                i += end + 2;
+            }
+            _ if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text
+                    .parse::<i64>()
+                    .map(Value::from)
+                    .or_else(|_| text.parse::<f64>().map(Value::from))
+                    .map_err(|_| anyhow!("表达式中的数字无效: {}", text))?;
+                tokens.push(Token::Literal(value));
+            }
+            _ if is_ident_char(c) => {
+                let start = i;
+                while i < chars.len() && is_ident_char(chars[i]) {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                tokens.push(match text.as_str() {
+                    "true" => Token::Literal(Value::Bool(true)),
+                    "false" => Token::Literal(Value::Bool(false)),
+                    "null" => Token::Literal(Value::Null),
+                    _ => Token::Ident(text),
+                });
+            }
+            _ => return Err(anyhow!("表达式中存在无法识别的字符 '{}': {}", c, input)),
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '[' | ']') || !c.is_ascii()
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(anyhow!("表达式中的括号没有闭合")),
+                }
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr> {
+        let left = self.parse_operand()?;
+        match self.peek() {
+            Some(Token::Op(op)) => {
+                let op = *op;
+                self.pos += 1;
+                Ok(Expr::Compare(left, op, self.parse_operand()?))
+            }
+            _ => Ok(Expr::Truthy(left)),
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand> {
+        match self.next() {
+            Some(Token::Ident(name)) => Ok(Operand::Ident(name)),
+            Some(Token::Column(name)) => Ok(Operand::Column(name)),
+            Some(Token::Literal(value)) => Ok(Operand::Literal(value)),
+            Some(token) => Err(anyhow!("表达式中存在多余的符号: {:?}", token)),
+            None => Err(anyhow!("表达式不完整")),
+        }
+    }
+}
+
+// 解析过滤表达式
+pub fn parse_expr(input: &str) -> Result<Expr> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+    };
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(anyhow!("表达式中存在多余的符号: {:?}", token));
+    }
+    Ok(expr)
+}
+
+// 查找列，优先精确匹配，其次忽略大小写匹配
+pub fn lookup<'a>(record: &'a Map<String, Value>, name: &str) -> Option<&'a Value> {
+    record.get(name).or_else(|| {
+        record
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    })
+}
+
+impl Expr {
+    pub fn eval(&self, record: &Map<String, Value>) -> Result<bool> {
+        Ok(match self {
+            Expr::And(left, right) => left.eval(record)? && right.eval(record)?,
+            Expr::Or(left, right) => left.eval(record)? || right.eval(record)?,
+            Expr::Not(expr) => !expr.eval(record)?,
+            Expr::Truthy(operand) => is_truthy(&operand.eval(record)?),
+            Expr::Compare(left, op, right) => {
+                let left = left.eval(record)?;
+                let right = right.eval(record)?;
+                match op {
+                    // null只能用 == 或 != 比较，大小比较总是不成立
+                    CompareOp::Gt | CompareOp::Ge | CompareOp::Lt | CompareOp::Le
+                        if left.is_null() || right.is_null() =>
+                    {
+                        false
+                    }
+                    CompareOp::Eq => compare_values(&left, &right) == Ordering::Equal,
+                    CompareOp::Ne => compare_values(&left, &right) != Ordering::Equal,
+                    CompareOp::Gt => compare_values(&left, &right) == Ordering::Greater,
+                    CompareOp::Ge => compare_values(&left, &right) != Ordering::Less,
+                    CompareOp::Lt => compare_values(&left, &right) == Ordering::Less,
+                    CompareOp::Le => compare_values(&left, &right) != Ordering::Greater,
+                    CompareOp::Contains => value_text(&left).contains(&value_text(&right)),
+                }
+            }
+        })
+    }
+
+    // 表达式中必须存在的列名，用于检查列是否存在
+    // 比较左侧及单独使用的标识符必须是列，避免列名拼写错误时被当作字符串静默匹配
+    pub fn columns(&self) -> Vec<&str> {
+        match self {
+            Expr::And(left, right) | Expr::Or(left, right) => {
+                let mut columns = left.columns();
+                columns.extend(right.columns());
+                columns
+            }
+            Expr::Not(expr) => expr.columns(),
+            Expr::Truthy(operand) => operand.required_column().into_iter().collect(),
+            Expr::Compare(left, _, right) => left
+                .required_column()
+                .into_iter()
+                .chain(right.column())
+                .collect(),
+        }
+    }
+}
+
+impl Operand {
+    fn eval(&self, record: &Map<String, Value>) -> Result<Value> {
+        match self {
+            Operand::Ident(name) => Ok(lookup(record, name)
+                .cloned()
+                .unwrap_or_else(|| Value::String(name.clone()))),
+            Operand::Column(name) => lookup(record, name)
+                .cloned()
+                .ok_or_else(|| anyhow!("表达式中的列不存在: {}", name)),
+            Operand::Literal(value) => Ok(value.clone()),
+        }
+    }
+
+    fn column(&self) -> Option<&str> {
+        match self {
+            Operand::Column(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn required_column(&self) -> Option<&str> {
+        match self {
+            Operand::Ident(name) | Operand::Column(name) => Some(name),
+            Operand::Literal(_) => None,
+        }
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        _ => true,
+    }
+}
+
+fn value_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+pub fn value_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+// 比较两个值：均为数字时按数值比较，否则按字符串比较
+pub fn compare_values(left: &Value, right: &Value) -> Ordering {
+    match (value_number(left), value_number(right)) {
+        (Some(l), Some(r)) => l.partial_cmp(&r).unwrap_or(Ordering::Equal),
+        _ => value_text(left).cmp(&value_text(right)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn record() -> Map<String, Value> {
+        let Value::Object(map) = json!({
+            "Name": "Gianluigi Buffon",
+            "Nationality": "Italy",
+            "Kit Number": 77,
+            "age": "41",
+            "active": false
+        }) else {
+            unreachable!()
+        };
+        map
+    }
+
+    #[test]
+    fn test_eval_expr() -> Result<()> {
+        let record = record();
+        let cases = [
+            ("age>30 && nationality==Italy", true),
+            ("age > 41", false),
+            ("age >= 41 && `Kit Number` < 100", true),
+            ("Nationality != 'Italy' || Name ~= Buffon", true),
+            ("!(age <= 40) && !active", true),
+            ("active", false),
+            ("Name == \"Gianluigi Buffon\"", true),
+            ("`Kit Number` == 77.0", true),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_expr(input)?.eval(&record)?, expected, "{}", input);
+        }
+        Ok(())
+    }
+
+    // 测试空单元格不参与大小比较，仍可与null比较是否相等
+    #[test]
+    fn test_eval_expr_null() -> Result<()> {
+        let Value::Object(record) = serde_json::json!({ "name": "Amy", "age": null }) else {
+            unreachable!()
+        };
+        let cases = [
+            ("age < 28", false),
+            ("age <= 28", false),
+            ("age > 28", false),
+            ("age >= 28", false),
+            ("!(age < 28)", true),
+            ("age == null", true),
+            ("age != null", false),
+            ("name != null", true),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_expr(input)?.eval(&record)?, expected, "{}", input);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_expr_errors() {
+        assert!(parse_expr("age > ").is_err());
+        assert!(parse_expr("(age > 1").is_err());
+        assert!(parse_expr("age > 1 1").is_err());
+        assert!(parse_expr("name == 'Italy").is_err());
+        assert!(parse_expr("`missing` == 1")
+            .unwrap()
+            .eval(&record())
+            .is_err());
+    }
+
+    #[test]
+    fn test_expr_columns() -> Result<()> {
+        let expr = parse_expr("Natonality==Italy && (age>3 || `Kit Number` == Name) && active")?;
+        assert_eq!(
+            expr.columns(),
+            vec!["Natonality", "age", "Kit Number", "active"]
+        );
+        assert_eq!(
+            parse_expr("'Italy' == Nationality")?.columns(),
+            Vec::<&str>::new()
+        );
+        Ok(())
+    }
+}
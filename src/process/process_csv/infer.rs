@@ -0,0 +1,135 @@
+use crate::CsvColumnType;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde_json::{Number, Value};
+
+// 根据字段内容推断类型：空值为null，其次依次尝试布尔值、整数、浮点数，否则保留字符串
+pub fn infer_value(field: &str) -> Value {
+    if field.is_empty() {
+        return Value::Null;
+    }
+    if let Some(b) = parse_bool(field) {
+        return Value::Bool(b);
+    }
+    if let Some(n) = parse_int(field) {
+        return Value::Number(n.into());
+    }
+    if let Some(n) = parse_float(field) {
+        return Value::Number(n);
+    }
+    Value::String(field.to_string())
+}
+
+// 按指定类型转换字段，空字段（非string类型）转换为null
+// 用户明确指定了数值类型，带前导0的数字（如 007）也按数值转换
+pub fn cast_value(field: &str, column_type: CsvColumnType) -> Result<Value> {
+    if column_type != CsvColumnType::String && field.trim().is_empty() {
+        return Ok(Value::Null);
+    }
+
+    let value = match column_type {
+        CsvColumnType::String => Some(Value::String(field.to_string())),
+        CsvColumnType::Int => field
+            .trim()
+            .parse::<i64>()
+            .ok()
+            .map(|n| Value::Number(n.into())),
+        CsvColumnType::Float => field
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number),
+        CsvColumnType::Bool => parse_bool(field.trim()).map(Value::Bool),
+        CsvColumnType::Date => parse_date(field.trim()).map(Value::String),
+    };
+
+    value.ok_or_else(|| anyhow!("无法将 \"{}\" 转换为 {} 类型", field, column_type))
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    match s {
+        "true" | "True" | "TRUE" => Some(true),
+        "false" | "False" | "FALSE" => Some(false),
+        _ => None,
+    }
+}
+
+// 推断类型时带前导0的数字（如邮编 007）保留为字符串，避免丢失信息
+fn parse_int(s: &str) -> Option<i64> {
+    let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+    if digits.len() > 1 && digits.starts_with('0') {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn parse_float(s: &str) -> Option<Number> {
+    // 排除 inf、nan 等非数字写法
+    if !s.bytes().any(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+    if digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.") {
+        return None;
+    }
+    s.parse::<f64>().ok().and_then(Number::from_f64)
+}
+
+// 支持 YYYY-MM-DD、YYYY-MM-DDTHH:MM:SS 以及 RFC3339 格式，统一输出为ISO 8601格式
+fn parse_date(s: &str) -> Option<String> {
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Some(date.format("%Y-%m-%d").to_string());
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(s) {
+        return Some(datetime.to_rfc3339());
+    }
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .map(|datetime| datetime.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_infer_value() {
+        assert_eq!(infer_value(""), Value::Null);
+        assert_eq!(infer_value("true"), json!(true));
+        assert_eq!(infer_value("FALSE"), json!(false));
+        assert_eq!(infer_value("30"), json!(30));
+        assert_eq!(infer_value("-12"), json!(-12));
+        assert_eq!(infer_value("1.5"), json!(1.5));
+        assert_eq!(infer_value("0.25"), json!(0.25));
+        assert_eq!(infer_value("007"), json!("007"));
+        assert_eq!(infer_value("nan"), json!("nan"));
+        assert_eq!(infer_value("Italy"), json!("Italy"));
+    }
+
+    #[test]
+    fn test_cast_value() -> Result<()> {
+        assert_eq!(cast_value("007", CsvColumnType::String)?, json!("007"));
+        assert_eq!(cast_value(" 42 ", CsvColumnType::Int)?, json!(42));
+        assert_eq!(cast_value("007", CsvColumnType::Int)?, json!(7));
+        assert_eq!(cast_value("-007", CsvColumnType::Int)?, json!(-7));
+        assert_eq!(cast_value("007.5", CsvColumnType::Float)?, json!(7.5));
+        assert!(cast_value("nan", CsvColumnType::Float).is_err());
+        assert_eq!(cast_value("42", CsvColumnType::Float)?, json!(42.0));
+        assert_eq!(cast_value("True", CsvColumnType::Bool)?, json!(true));
+        assert_eq!(cast_value("", CsvColumnType::Int)?, Value::Null);
+        assert_eq!(
+            cast_value("1990-04-18", CsvColumnType::Date)?,
+            json!("1990-04-18")
+        );
+        assert_eq!(
+            cast_value("1990-04-18 08:30:00", CsvColumnType::Date)?,
+            json!("1990-04-18T08:30:00")
+        );
+        assert!(cast_value("abc", CsvColumnType::Int).is_err());
+        assert!(cast_value("Apr 18, 1990", CsvColumnType::Date).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,407 @@
+use std::io::{Read, Write};
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+use crate::{
+    process::process_text::{Blake3, TextSign},
+    utils::{get_reader_from_path, get_writer_from_path},
+};
+
+use super::{
+    dialect::{build_reader_with_delimiter, build_writer, read_headers},
+    CsvDialect,
+};
+
+// 生成假数据的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvFakeKind {
+    Name,
+    Email,
+    Phone,
+}
+
+// 列的脱敏方式
+#[derive(Debug, Clone, PartialEq)]
+pub enum CsvMaskStrategy {
+    // 替换为固定的内容
+    Redact(String),
+    // 使用带密钥的BLAKE3哈希，输出十六进制
+    Hash,
+    // 替换为格式相同的假数据
+    Fake(CsvFakeKind),
+    // 日期偏移不超过指定天数，所有日期偏移相同的天数以保留间隔
+    DateShift(i64),
+    // 数值增加不超过指定百分比的噪声
+    Noise(f64),
+}
+
+// 脱敏规则
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvMaskRule {
+    pub column: String,
+    pub strategy: CsvMaskStrategy,
+}
+
+// 脱敏使用的密钥，相同的密钥对相同的值总是生成相同的结果
+#[derive(Debug, Clone)]
+pub enum CsvMaskKey {
+    // 由种子字符串生成密钥
+    Seed(String),
+    // 从文件读取32字节的密钥，与 text sign 的blake3密钥相同
+    File(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvMaskConfig {
+    pub dialect: CsvDialect,
+    pub rules: Vec<CsvMaskRule>,
+    pub key: CsvMaskKey,
+}
+
+const EN_FIRST_NAMES: [&str; 16] = [
+    "James", "Mary", "John", "Linda", "Robert", "Emma", "David", "Olivia", "Michael", "Sophia",
+    "William", "Ava", "Daniel", "Mia", "Thomas", "Grace",
+];
+const EN_LAST_NAMES: [&str; 16] = [
+    "Smith", "Johnson", "Brown", "Taylor", "Miller", "Wilson", "Moore", "Clark", "Lewis", "Walker",
+    "Hall", "Young", "King", "Wright", "Green", "Baker",
+];
+const CN_SURNAMES: [&str; 16] = [
+    "王", "李", "张", "刘", "陈", "杨", "黄", "赵", "吴", "周", "徐", "孙", "马", "朱", "胡", "郭",
+];
+const CN_GIVEN_NAMES: [&str; 16] = [
+    "伟", "芳", "娜", "敏", "静", "磊", "洋", "勇", "艳", "杰", "涛", "明", "超", "秀", "霞", "平",
+];
+const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%Y/%m/%d", "%Y%m%d", "%d/%m/%Y"];
+const DATETIME_FORMATS: [&str; 3] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y/%m/%d %H:%M:%S",
+];
+
+struct Masker {
+    blake3: Blake3,
+}
+
+impl Masker {
+    fn new(key: &CsvMaskKey) -> Result<Self> {
+        let blake3 = match key {
+            CsvMaskKey::Seed(seed) => Blake3::new(*blake3::hash(seed.as_bytes()).as_bytes()),
+            CsvMaskKey::File(path) => {
+                let key = std::fs::read(path)?;
+                if key.len() < 32 {
+                    return Err(anyhow!("密钥文件至少需要32字节: {}", path));
+                }
+                Blake3::try_new(&key)?
+            }
+        };
+        Ok(Self { blake3 })
+    }
+
+    fn digest(&self, value: &str) -> Result<Vec<u8>> {
+        self.blake3.sign(&mut value.as_bytes())
+    }
+
+    // 不同用途使用不同的前缀，避免哈希输出与假数据的随机数相关
+    fn random(&self, purpose: &str, value: &str) -> Result<u64> {
+        let digest = self.digest(&format!("{}\0{}", purpose, value))?;
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn mask(&self, value: &str, strategy: &CsvMaskStrategy) -> Result<String> {
+        // 空值保持为空
+        if value.is_empty() {
+            return Ok(String::new());
+        }
+        match strategy {
+            CsvMaskStrategy::Redact(text) => Ok(text.clone()),
+            CsvMaskStrategy::Hash => Ok(self
+                .digest(value)?
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect()),
+            CsvMaskStrategy::Fake(CsvFakeKind::Name) => self.fake_name(value),
+            CsvMaskStrategy::Fake(CsvFakeKind::Email) => self.fake_email(value),
+            CsvMaskStrategy::Fake(CsvFakeKind::Phone) => self.fake_phone(value),
+            CsvMaskStrategy::DateShift(days) => self.shift_date(value, *days),
+            CsvMaskStrategy::Noise(percent) => self.add_noise(value, *percent),
+        }
+    }
+
+    // 包含中文时生成相同字数的中文姓名，否则生成与原单词数相同的英文姓名
+    fn fake_name(&self, value: &str) -> Result<String> {
+        let random = self.random("name", value)?;
+        let pick =
+            |names: &[&'static str], shift: u32| names[(random >> shift) as usize % names.len()];
+        if value
+            .chars()
+            .any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c))
+        {
+            let mut name = pick(&CN_SURNAMES, 0).to_string();
+            name.push_str(pick(&CN_GIVEN_NAMES, 8));
+            if value.chars().count() > 2 {
+                name.push_str(pick(&CN_GIVEN_NAMES, 16));
+            }
+            return Ok(name);
+        }
+        let first = pick(&EN_FIRST_NAMES, 0);
+        if value.split_whitespace().count() < 2 {
+            return Ok(first.to_string());
+        }
+        Ok(format!("{} {}", first, pick(&EN_LAST_NAMES, 8)))
+    }
+
+    // 姓名之后加上哈希的十六进制，避免不同的邮箱生成相同的结果
+    fn fake_email(&self, value: &str) -> Result<String> {
+        let digest = self.digest(&format!("email\0{}", value))?;
+        let suffix: String = digest[2..10].iter().map(|b| format!("{:02x}", b)).collect();
+        Ok(format!(
+            "{}.{}.{}@example.com",
+            EN_FIRST_NAMES[digest[0] as usize % EN_FIRST_NAMES.len()].to_lowercase(),
+            EN_LAST_NAMES[digest[1] as usize % EN_LAST_NAMES.len()].to_lowercase(),
+            suffix
+        ))
+    }
+
+    // 替换所有数字，保留 + - 空格 括号等格式字符
+    fn fake_phone(&self, value: &str) -> Result<String> {
+        let digest = self.digest(&format!("phone\0{}", value))?;
+        Ok(value
+            .chars()
+            .enumerate()
+            .map(|(i, c)| match c {
+                '0'..='9' => char::from(b'0' + digest[i % digest.len()] % 10),
+                c => c,
+            })
+            .collect())
+    }
+
+    // 偏移天数只由密钥决定，所有文件的日期偏移相同
+    fn shift_date(&self, value: &str, days: i64) -> Result<String> {
+        let days = i128::from(days);
+        let offset = i128::from(self.random("date", "")?) % (2 * days + 1) - days;
+        let shift = i64::try_from(offset)
+            .ok()
+            .and_then(Duration::try_days)
+            .ok_or_else(|| anyhow!("日期偏移天数过大: {}", days))?;
+        let overflow = || anyhow!("日期偏移后超出范围: {}", value);
+        for format in DATETIME_FORMATS {
+            if let Ok(datetime) = NaiveDateTime::parse_from_str(value, format) {
+                let datetime = datetime.checked_add_signed(shift).ok_or_else(overflow)?;
+                return Ok(datetime.format(format).to_string());
+            }
+        }
+        for format in DATE_FORMATS {
+            if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+                let date = date.checked_add_signed(shift).ok_or_else(overflow)?;
+                return Ok(date.format(format).to_string());
+            }
+        }
+        Err(anyhow!("无法识别的日期: {}", value))
+    }
+
+    // 保留原值的小数位数，整数仍输出为整数
+    fn add_noise(&self, value: &str, percent: f64) -> Result<String> {
+        let number: f64 = value
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("不是数值: {}", value))?;
+        let ratio = (self.random("noise", value)? % 2_000_001) as f64 / 1_000_000.0 - 1.0;
+        let noisy = number * (1.0 + ratio * percent / 100.0);
+        let decimals = value.trim().split_once('.').map_or(0, |(_, d)| d.len());
+        Ok(format!("{:.*}", decimals, noisy))
+    }
+}
+
+// 按规则对指定列脱敏，其他列保持不变，返回处理的行数
+pub fn mask_csv<R: Read, W: Write>(input: R, output: W, config: &CsvMaskConfig) -> Result<usize> {
+    let masker = Masker::new(&config.key)?;
+    let (mut reader, delimiter) = build_reader_with_delimiter(input, &config.dialect)?;
+    let headers = read_headers(&mut reader, &config.dialect)?;
+    let rules = config
+        .rules
+        .iter()
+        .map(|rule| {
+            headers
+                .iter()
+                .position(|header| header == rule.column)
+                .map(|index| (index, rule))
+                .ok_or_else(|| anyhow!("列不存在: {}", rule.column))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut writer = build_writer(output, &config.dialect, delimiter);
+    if config.dialect.has_headers {
+        writer.write_record(&headers)?;
+    }
+    let mut count = 0;
+    for record in reader.records() {
+        let record = record?;
+        let mut fields: Vec<String> = record.iter().map(String::from).collect();
+        for (index, rule) in &rules {
+            if let Some(field) = fields.get_mut(*index) {
+                *field = masker.mask(field, &rule.strategy).map_err(|e| {
+                    let line = record.position().map(|p| p.line()).unwrap_or_default();
+                    anyhow!("第{}行 \"{}\" 列: {}", line, rule.column, e)
+                })?;
+            }
+        }
+        writer.write_record(&fields)?;
+        count += 1;
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+// 对csv文件脱敏
+pub fn mask_csv_in_file(
+    input_path: &str,
+    save_path: &str,
+    config: &CsvMaskConfig,
+) -> Result<usize> {
+    mask_csv(
+        get_reader_from_path(input_path)?,
+        get_writer_from_path(save_path)?,
+        config,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn mask(input: &str, rules: Vec<CsvMaskRule>, seed: &str) -> Result<Vec<Vec<String>>> {
+        let config = CsvMaskConfig {
+            dialect: CsvDialect::default(),
+            rules,
+            key: CsvMaskKey::Seed(seed.to_string()),
+        };
+        let mut output = Vec::new();
+        mask_csv(input.as_bytes(), &mut output, &config)?;
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        reader
+            .records()
+            .map(|record| Ok(record?.iter().map(String::from).collect()))
+            .collect()
+    }
+
+    fn rule(column: &str, strategy: CsvMaskStrategy) -> CsvMaskRule {
+        CsvMaskRule {
+            column: column.to_string(),
+            strategy,
+        }
+    }
+
+    #[test]
+    fn test_mask_csv() -> Result<()> {
+        let input = "name,email,phone,birthday,salary,note\n\
+                     张三,zs@corp.cn,+86 138-1234-5678,1990-05-01,12000.50,secret\n\
+                     Tom Hanks,tom@corp.cn,(555) 010-2000,1985/12/31,8000,\n";
+        let rules = vec![
+            rule("name", CsvMaskStrategy::Fake(CsvFakeKind::Name)),
+            rule("email", CsvMaskStrategy::Fake(CsvFakeKind::Email)),
+            rule("phone", CsvMaskStrategy::Fake(CsvFakeKind::Phone)),
+            rule("birthday", CsvMaskStrategy::DateShift(30)),
+            rule("salary", CsvMaskStrategy::Noise(10.0)),
+            rule("note", CsvMaskStrategy::Redact("***".to_string())),
+        ];
+        let rows = mask(input, rules.clone(), "seed")?;
+        assert_eq!(rows, mask(input, rules.clone(), "seed")?);
+        assert_ne!(rows, mask(input, rules, "other")?);
+
+        let (zhang, tom) = (&rows[0], &rows[1]);
+        assert_eq!(zhang[0].chars().count(), 2);
+        assert_eq!(tom[0].split(' ').count(), 2);
+        assert!(zhang[1].ends_with("@example.com"));
+        assert!(zhang[2].starts_with('+') && zhang[2].len() == "+86 138-1234-5678".len());
+        assert_eq!(&tom[2][..1], "(");
+
+        let old = NaiveDate::parse_from_str("1990-05-01", "%Y-%m-%d")?;
+        let new = NaiveDate::parse_from_str(&zhang[3], "%Y-%m-%d")?;
+        assert!((new - old).num_days().abs() <= 30);
+        // 所有日期偏移相同的天数
+        let old = NaiveDate::parse_from_str("1985/12/31", "%Y/%m/%d")?;
+        assert_eq!(
+            NaiveDate::parse_from_str(&tom[3], "%Y/%m/%d")? - old,
+            new - NaiveDate::parse_from_str("1990-05-01", "%Y-%m-%d")?
+        );
+
+        let salary: f64 = zhang[4].parse()?;
+        assert!((salary - 12000.5).abs() <= 1200.05);
+        assert_eq!(zhang[4].split_once('.').map(|(_, d)| d.len()), Some(2));
+        assert!(!tom[4].contains('.'));
+        assert_eq!(zhang[5], "***");
+        assert_eq!(tom[5], "");
+        Ok(())
+    }
+
+    // 测试不同的邮箱生成不同的假邮箱
+    #[test]
+    fn test_mask_fake_email_unique() -> Result<()> {
+        let input: String = (0..10_000).fold("email\n".to_string(), |mut input, i| {
+            input.push_str(&format!("user{}@corp.cn\n", i));
+            input
+        });
+        let rows = mask(
+            &input,
+            vec![rule("email", CsvMaskStrategy::Fake(CsvFakeKind::Email))],
+            "seed",
+        )?;
+        let emails: std::collections::HashSet<&String> = rows.iter().map(|row| &row[0]).collect();
+        assert_eq!(emails.len(), rows.len());
+        Ok(())
+    }
+
+    // 测试偏移天数过大时返回错误而不是panic
+    #[test]
+    fn test_mask_date_shift_overflow() {
+        let input = "birthday\n1990-05-01\n";
+        for days in [1_000_000_000, i64::MAX] {
+            let rules = vec![rule("birthday", CsvMaskStrategy::DateShift(days))];
+            assert!(mask(input, rules, "seed").is_err());
+        }
+    }
+
+    // 测试密钥文件不足32字节时返回错误
+    #[test]
+    fn test_mask_short_key_file() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("rrcli_mask_key_{}", std::process::id()));
+        std::fs::write(&path, "short")?;
+        let config = CsvMaskConfig {
+            dialect: CsvDialect::default(),
+            rules: vec![rule("id", CsvMaskStrategy::Hash)],
+            key: CsvMaskKey::File(path.to_string_lossy().into_owned()),
+        };
+        let result = mask_csv("id\n1\n".as_bytes(), Vec::new(), &config);
+        std::fs::remove_file(&path)?;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("密钥文件至少需要32字节"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mask_hash() -> Result<()> {
+        let input = "id,email\n1,a@b.c\n2,a@b.c\n";
+        let rows = mask(input, vec![rule("email", CsvMaskStrategy::Hash)], "seed")?;
+        assert_eq!(rows[0][1], rows[1][1]);
+        assert_eq!(rows[0][1].len(), 64);
+        let key = blake3::hash(b"seed");
+        assert_eq!(
+            rows[0][1],
+            blake3::keyed_hash(key.as_bytes(), b"a@b.c")
+                .to_hex()
+                .as_str()
+        );
+
+        let input = "id,birthday\n1,not a date\n";
+        let rules = vec![rule("birthday", CsvMaskStrategy::DateShift(10))];
+        assert!(mask(input, rules, "seed").is_err());
+        Ok(())
+    }
+}
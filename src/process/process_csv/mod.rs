@@ -0,0 +1,704 @@
+mod batch;
+mod dialect;
+mod diff;
+mod encoding;
+mod filter;
+mod infer;
+mod mask;
+mod plumbing;
+mod progress;
+mod query;
+mod schema;
+mod stats;
+mod table;
+mod transform;
+mod unflatten;
+mod view;
+mod watch;
+mod writer;
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Read, Write},
+};
+
+use anyhow::{anyhow, Result};
+use csv::{Reader, StringRecord};
+use serde_json::{Map, Value};
+
+use crate::{
+    utils::{get_reader_from_path, get_writer_from_path},
+    CsvColumnType, CsvEncoding, CsvFormatType,
+};
+
+pub(crate) use self::encoding::{decode_reader, EncodingWriter};
+pub use self::{
+    batch::{
+        convert_csv_batch, convert_csv_batch_files, glob_files, is_glob_pattern, CsvBatchConfig,
+        CsvBatchReport,
+    },
+    dialect::CsvDialect,
+    diff::diff_csv_in_file,
+    mask::{
+        mask_csv_in_file, CsvFakeKind, CsvMaskConfig, CsvMaskKey, CsvMaskRule, CsvMaskStrategy,
+    },
+    plumbing::{
+        cat_csv_in_file, dedup_csv_in_file, join_csv_in_file, split_csv_in_file, CsvSplitBy,
+    },
+    query::{query_csv_in_file, CsvTable},
+    schema::{
+        schema_csv_in_file, validate_csv_in_file, CsvSchemaConfig, CsvValidationError,
+        CsvValidationReport,
+    },
+    stats::{stats_csv_in_file, CsvStatsConfig},
+    table::TableStyle,
+    transform::CsvSortKey,
+    view::{view_csv, view_csv_in_file, CsvViewConfig},
+    watch::watch_csv_inputs,
+};
+
+use self::{
+    dialect::{build_reader, column_name, read_headers},
+    infer::{cast_value, infer_value},
+    progress::Progress,
+    transform::RecordTransform,
+    unflatten::unflatten,
+    writer::RecordWriter,
+};
+
+// csv转换配置
+#[derive(Debug, Clone)]
+pub struct CsvConvertConfig {
+    // 是否推断字段类型
+    pub infer_types: bool,
+    // 指定列类型，优先于类型推断
+    pub column_types: HashMap<String, CsvColumnType>,
+    // toml输出的根表名
+    pub toml_key: String,
+    // toml输出时作为表名的列，指定后每行输出为 [toml_key.列值]
+    pub toml_key_column: Option<String>,
+    // 是否在标准错误输出转换进度
+    pub progress: bool,
+    // csv方言
+    pub dialect: CsvDialect,
+    // 按分隔符及 [下标] 还原嵌套结构时使用的分隔符，如 address.city
+    pub unflatten: Option<String>,
+    // 只输出的列
+    pub select: Vec<String>,
+    // 列重命名，原列名 -> 新列名
+    pub rename: Vec<(String, String)>,
+    // 过滤表达式，如 age>30 && nationality==Italy
+    pub filter: Option<String>,
+    // 排序字段，指定后需要读取全部记录再输出
+    pub sort_by: Vec<CsvSortKey>,
+}
+
+impl Default for CsvConvertConfig {
+    fn default() -> Self {
+        Self {
+            infer_types: false,
+            column_types: HashMap::new(),
+            toml_key: "data".to_string(),
+            toml_key_column: None,
+            progress: false,
+            dialect: CsvDialect::default(),
+            unflatten: None,
+            select: Vec::new(),
+            rename: Vec::new(),
+            filter: None,
+            sort_by: Vec::new(),
+        }
+    }
+}
+
+// 将一行csv数据转换为json对象的字段
+fn record_to_map(
+    headers: &StringRecord,
+    record: &StringRecord,
+    config: &CsvConvertConfig,
+) -> Result<Map<String, Value>> {
+    let mut map = Map::with_capacity(headers.len());
+    for (i, field) in record.iter().enumerate() {
+        // 字段数多于表头时（flexible模式）使用生成的列名
+        let header = match headers.get(i) {
+            Some(header) => header.to_string(),
+            None => column_name(i + 1),
+        };
+        let value = match config.column_types.get(&header) {
+            Some(column_type) => cast_value(field, *column_type).map_err(|e| {
+                let line = record.position().map(|p| p.line()).unwrap_or_default();
+                anyhow!("第{}行 \"{}\" 列: {}", line, header, e)
+            })?,
+            None if config.infer_types => infer_value(field),
+            None => Value::String(field.to_string()),
+        };
+        map.insert(header, value);
+    }
+    // 字段数少于表头时缺少的列为null
+    for header in headers.iter().skip(record.len()) {
+        map.insert(header.to_string(), Value::Null);
+    }
+    Ok(map)
+}
+
+// 根据配置还原嵌套结构
+fn map_to_value(map: Map<String, Value>, config: &CsvConvertConfig) -> Result<Value> {
+    match &config.unflatten {
+        Some(separator) => unflatten(map, separator),
+        None => Ok(Value::Object(map)),
+    }
+}
+
+// 检查指定类型的列都存在于表头中
+fn check_column_types(headers: &StringRecord, config: &CsvConvertConfig) -> Result<()> {
+    match config
+        .column_types
+        .keys()
+        .find(|column| !headers.iter().any(|header| header == column.as_str()))
+    {
+        Some(column) => Err(anyhow!("指定类型的列不存在: {}", column)),
+        None => Ok(()),
+    }
+}
+
+// 读取csv的所有记录并转换为json对象
+pub fn read_csv_records<R: Read>(
+    reader: &mut Reader<R>,
+    config: &CsvConvertConfig,
+) -> Result<Vec<Value>> {
+    let headers = read_headers(reader, &config.dialect)?;
+    check_column_types(&headers, config)?;
+
+    let mut result = Vec::with_capacity(128);
+    for row in reader.records() {
+        let row_data = row?;
+        let map = record_to_map(&headers, &row_data, config)?;
+        result.push(map_to_value(map, config)?);
+    }
+    Ok(result)
+}
+
+// 流式转换csv：逐条读取记录并写出，内存占用与文件大小无关，返回转换的记录数
+pub fn convert_csv_stream<R: Read, W: Write>(
+    input: R,
+    output: W,
+    format_type: CsvFormatType,
+    config: &CsvConvertConfig,
+    input_size: Option<u64>,
+) -> Result<usize> {
+    let is_binary = matches!(
+        format_type,
+        CsvFormatType::MessagePack | CsvFormatType::Cbor
+    );
+    // 输出的编码只用于文本格式
+    if is_binary && config.dialect.output_encoding != CsvEncoding::Utf8 {
+        return Err(anyhow!("二进制格式 {} 不支持指定输出编码", format_type));
+    }
+
+    let mut reader = build_reader(input, &config.dialect)?;
+    let headers = read_headers(&mut reader, &config.dialect)?;
+    check_column_types(&headers, config)?;
+
+    let mut writer = RecordWriter::new(
+        EncodingWriter::new(output, config.dialect.output_encoding),
+        format_type,
+        config.toml_key.clone(),
+        config.toml_key_column.clone(),
+    );
+    let mut progress = config.progress.then(|| Progress::new(input_size));
+
+    let transform = RecordTransform::new(&headers, config)?;
+    // 排序时需要缓存全部记录
+    let mut sorted = Vec::new();
+
+    let mut record = StringRecord::new();
+    while reader.read_record(&mut record)? {
+        let map = record_to_map(&headers, &record, config)?;
+        if let Some((sort_values, map)) = transform.apply(map)? {
+            if transform.is_sorted() {
+                sorted.push((sort_values, map));
+            } else {
+                writer.write(map_to_value(map, config)?)?;
+            }
+        }
+        if let Some(progress) = progress.as_mut() {
+            progress.update(reader.position().byte());
+        }
+    }
+    if let Some(progress) = progress.as_mut() {
+        progress.finish(reader.position().byte());
+    }
+
+    sorted.sort_by(|(left, _), (right, _)| transform.compare(left, right));
+    for (_, map) in sorted {
+        writer.write(map_to_value(map, config)?)?;
+    }
+
+    writer.finish()
+}
+
+// 转换csv文件至其他格式的文件，路径为“-”时从标准输入读取或写出到标准输出
+pub fn convert_csv_in_file(
+    input_path: String,
+    save_path: String,
+    format_type: CsvFormatType,
+    config: &CsvConvertConfig,
+) -> Result<()> {
+    let input_size = if input_path == "-" {
+        None
+    } else {
+        fs::metadata(&input_path).ok().map(|meta| meta.len())
+    };
+    let input = get_reader_from_path(&input_path)?;
+    let output = get_writer_from_path(&save_path)?;
+    convert_csv_stream(input, output, format_type, config, input_size)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    // 转换csv文件并返回转换结果
+    fn convert_csv(
+        input_path: String,
+        format_type: CsvFormatType,
+        config: &CsvConvertConfig,
+    ) -> Result<String> {
+        let mut output = Vec::new();
+        convert_csv_stream(
+            fs::File::open(input_path)?,
+            &mut output,
+            format_type,
+            config,
+            None,
+        )?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    // 测试转换csv文件至json格式的文件
+    #[test]
+    fn test_convert_csv_json() -> Result<()> {
+        // 准备测试数据
+        let input_path = "./fixtures/process_csv/test.csv".to_string();
+        let expected_result = fs::read_to_string("./fixtures/process_csv/test.json")?;
+
+        // 调用函数进行转换
+        let result = convert_csv(
+            input_path,
+            CsvFormatType::Json,
+            &CsvConvertConfig::default(),
+        )?;
+
+        // 验证转换结果是否符合预期
+        assert_eq!(format!("{}\n", result), expected_result);
+
+        Ok(())
+    }
+
+    // 测试转换csv文件至yaml格式的文件
+    #[test]
+    fn test_convert_csv_yaml() -> Result<()> {
+        // 准备测试数据
+        let input_path = "./fixtures/process_csv/test.csv".to_string();
+        let expected_result = fs::read_to_string("./fixtures/process_csv/test.yaml")?;
+
+        // 调用函数进行转换
+        let result = convert_csv(
+            input_path,
+            CsvFormatType::Yaml,
+            &CsvConvertConfig::default(),
+        )?;
+
+        // 验证转换结果是否符合预期
+        assert_eq!(format!("{}", result), expected_result);
+        Ok(())
+    }
+
+    // 测试转换csv文件至toml格式的文件
+    #[test]
+    fn test_convert_csv_toml() -> Result<()> {
+        // 准备测试数据
+        let input_path = "./fixtures/process_csv/test.csv".to_string();
+        let expected_result = fs::read_to_string("./fixtures/process_csv/test.toml")?;
+
+        // 调用函数进行转换
+        let config = CsvConvertConfig {
+            toml_key: "players".to_string(),
+            ..Default::default()
+        };
+        let result = convert_csv(input_path, CsvFormatType::Toml, &config)?;
+
+        // 验证转换结果是否符合预期
+        assert_eq!(format!("{}", result), expected_result);
+
+        Ok(())
+    }
+
+    // 测试类型推断以及指定列类型
+    #[test]
+    fn test_convert_csv_infer_types() -> Result<()> {
+        let input_path = "./fixtures/process_csv/test.csv".to_string();
+        let config = CsvConvertConfig {
+            infer_types: true,
+            column_types: HashMap::from([("Kit Number".to_string(), CsvColumnType::Float)]),
+            ..Default::default()
+        };
+
+        let result = convert_csv(input_path, CsvFormatType::Json, &config)?;
+        let result: Value = serde_json::from_str(&result)?;
+
+        assert_eq!(result[0]["Kit Number"], serde_json::json!(1.0));
+        assert_eq!(result[0]["Name"], "Wojciech Szczesny");
+
+        Ok(())
+    }
+
+    // 测试指定类型转换失败时返回错误
+    #[test]
+    fn test_convert_csv_invalid_column_type() {
+        let input_path = "./fixtures/process_csv/test.csv".to_string();
+        let config = CsvConvertConfig {
+            infer_types: true,
+            column_types: HashMap::from([("Name".to_string(), CsvColumnType::Int)]),
+            ..Default::default()
+        };
+
+        let result = convert_csv(input_path, CsvFormatType::Json, &config);
+        assert!(result.is_err());
+    }
+
+    // 测试以指定列作为toml表名
+    #[test]
+    fn test_convert_csv_toml_keyed() -> Result<()> {
+        let input_path = "./fixtures/process_csv/test.csv".to_string();
+        let config = CsvConvertConfig {
+            infer_types: true,
+            toml_key: "players".to_string(),
+            toml_key_column: Some("Name".to_string()),
+            ..Default::default()
+        };
+
+        let result = convert_csv(input_path, CsvFormatType::Toml, &config)?;
+        let table: toml::Table = toml::from_str(&result)?;
+
+        assert!(result.contains("[players.\"Gianluigi Buffon\"]"));
+        assert_eq!(
+            table["players"]["Mattia Perin"]["Kit Number"].as_integer(),
+            Some(37)
+        );
+        assert!(table["players"]["Mattia Perin"].get("Name").is_none());
+
+        Ok(())
+    }
+
+    // 测试作为表名的列存在重复值时返回错误
+    #[test]
+    fn test_convert_csv_toml_keyed_duplicate() {
+        let input_path = "./fixtures/process_csv/test.csv".to_string();
+        let config = CsvConvertConfig {
+            toml_key_column: Some("Nationality".to_string()),
+            ..Default::default()
+        };
+
+        let result = convert_csv(input_path, CsvFormatType::Toml, &config);
+        assert!(result.is_err());
+    }
+
+    // 测试开启类型推断（命令行默认配置）时空单元格输出toml会省略该字段
+    #[test]
+    fn test_convert_csv_toml_empty_cell() -> Result<()> {
+        let input = "name,age,city\nBob,,Rome\nAmy,30,\n";
+        let config = CsvConvertConfig {
+            infer_types: true,
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        convert_csv_stream(
+            input.as_bytes(),
+            &mut output,
+            CsvFormatType::Toml,
+            &config,
+            None,
+        )?;
+        let table: toml::Table = toml::from_str(&String::from_utf8(output)?)?;
+        let data = table["data"].as_array().unwrap();
+        assert!(data[0].get("age").is_none());
+        assert_eq!(data[0]["city"].as_str(), Some("Rome"));
+        assert!(data[1].get("city").is_none());
+        assert_eq!(data[1]["age"].as_integer(), Some(30));
+        Ok(())
+    }
+
+    // 测试ndjson逐行输出
+    #[test]
+    fn test_convert_csv_ndjson() -> Result<()> {
+        let input = "name,age\nTom,30\nJerry,\n";
+        let config = CsvConvertConfig {
+            infer_types: true,
+            ..Default::default()
+        };
+
+        let mut output = Vec::new();
+        let count = convert_csv_stream(
+            input.as_bytes(),
+            &mut output,
+            CsvFormatType::Ndjson,
+            &config,
+            None,
+        )?;
+
+        assert_eq!(count, 2);
+        assert_eq!(
+            String::from_utf8(output)?,
+            "{\"name\":\"Tom\",\"age\":30}\n{\"name\":\"Jerry\",\"age\":null}\n"
+        );
+        Ok(())
+    }
+
+    // 测试没有记录时输出空数组
+    #[test]
+    fn test_convert_csv_stream_empty() -> Result<()> {
+        let config = CsvConvertConfig::default();
+        for (format, expected) in [(CsvFormatType::Json, "[]"), (CsvFormatType::Yaml, "[]\n")] {
+            let mut output = Vec::new();
+            convert_csv_stream("name,age\n".as_bytes(), &mut output, format, &config, None)?;
+            assert_eq!(String::from_utf8(output)?, expected);
+        }
+        Ok(())
+    }
+
+    // 测试分号分隔、注释行、去除空白以及不规则行
+    #[test]
+    fn test_convert_csv_dialect() -> Result<()> {
+        let input = "# exported by excel\nname; age\nTom; 30\nJerry\nSpike; 5; dog\n";
+        let config = CsvConvertConfig {
+            infer_types: true,
+            dialect: CsvDialect {
+                comment: Some(b'#'),
+                flexible: true,
+                trim: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut output = Vec::new();
+        convert_csv_stream(
+            input.as_bytes(),
+            &mut output,
+            CsvFormatType::Ndjson,
+            &config,
+            None,
+        )?;
+
+        assert_eq!(
+            String::from_utf8(output)?,
+            "{\"name\":\"Tom\",\"age\":30}\n\
+             {\"name\":\"Jerry\",\"age\":null}\n\
+             {\"name\":\"Spike\",\"age\":5,\"col3\":\"dog\"}\n"
+        );
+        Ok(())
+    }
+
+    // 测试无表头的tsv文件
+    #[test]
+    fn test_convert_csv_no_header() -> Result<()> {
+        let config = CsvConvertConfig {
+            dialect: CsvDialect {
+                has_headers: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut output = Vec::new();
+        convert_csv_stream(
+            "a\tb\nc\td\n".as_bytes(),
+            &mut output,
+            CsvFormatType::Ndjson,
+            &config,
+            None,
+        )?;
+
+        assert_eq!(
+            String::from_utf8(output)?,
+            "{\"col1\":\"a\",\"col2\":\"b\"}\n{\"col1\":\"c\",\"col2\":\"d\"}\n"
+        );
+        Ok(())
+    }
+
+    // 测试根据列名还原嵌套结构
+    #[test]
+    fn test_convert_csv_unflatten() -> Result<()> {
+        let input = "name,address.city,tags[0],tags[1]\nBuffon,Turin,goalkeeper,captain\n";
+        let config = CsvConvertConfig {
+            unflatten: Some(".".to_string()),
+            ..Default::default()
+        };
+
+        let mut output = Vec::new();
+        convert_csv_stream(
+            input.as_bytes(),
+            &mut output,
+            CsvFormatType::Ndjson,
+            &config,
+            None,
+        )?;
+
+        assert_eq!(
+            String::from_utf8(output)?,
+            "{\"name\":\"Buffon\",\"address\":{\"city\":\"Turin\"},\"tags\":[\"goalkeeper\",\"captain\"]}\n"
+        );
+        Ok(())
+    }
+
+    // 测试转换csv文件至xml、markdown、html格式的文件
+    #[test]
+    fn test_convert_csv_text_tables() -> Result<()> {
+        for (format, expected_path) in [
+            (CsvFormatType::Xml, "./fixtures/process_csv/test.xml"),
+            (CsvFormatType::Markdown, "./fixtures/process_csv/test.md"),
+            (CsvFormatType::Html, "./fixtures/process_csv/test.html"),
+        ] {
+            let input_path = "./fixtures/process_csv/test.csv".to_string();
+            let expected_result = fs::read_to_string(expected_path)?;
+
+            let result = convert_csv(input_path, format, &CsvConvertConfig::default())?;
+
+            assert_eq!(result, expected_result);
+        }
+        Ok(())
+    }
+
+    // 测试转换为MessagePack、CBOR后能解码回与json一致的数据
+    #[test]
+    fn test_convert_csv_binary_round_trip() -> Result<()> {
+        let expected: Value =
+            serde_json::from_str(&fs::read_to_string("./fixtures/process_csv/test.json")?)?;
+
+        for format in [CsvFormatType::MessagePack, CsvFormatType::Cbor] {
+            let mut output = Vec::new();
+            convert_csv_stream(
+                fs::File::open("./fixtures/process_csv/test.csv")?,
+                &mut output,
+                format,
+                &CsvConvertConfig::default(),
+                None,
+            )?;
+
+            let decoded: Value = match format {
+                CsvFormatType::MessagePack => rmp_serde::from_slice(&output)?,
+                _ => ciborium::from_reader(output.as_slice())?,
+            };
+            assert_eq!(decoded, expected);
+        }
+        Ok(())
+    }
+
+    // 测试列选择、重命名、过滤以及排序
+    #[test]
+    fn test_convert_csv_select_filter_sort() -> Result<()> {
+        let config = CsvConvertConfig {
+            infer_types: true,
+            select: vec!["name".to_string(), "Kit Number".to_string()],
+            rename: vec![("Kit Number".to_string(), "number".to_string())],
+            filter: Some("`Kit Number` > 10 && nationality == Italy".to_string()),
+            sort_by: vec![CsvSortKey {
+                column: "kit number".to_string(),
+                descending: true,
+            }],
+            ..Default::default()
+        };
+
+        let mut output = Vec::new();
+        convert_csv_stream(
+            fs::File::open("./fixtures/process_csv/test.csv")?,
+            &mut output,
+            CsvFormatType::Ndjson,
+            &config,
+            None,
+        )?;
+
+        assert_eq!(
+            String::from_utf8(output)?,
+            "{\"Name\":\"Gianluigi Buffon\",\"number\":77}\n{\"Name\":\"Mattia Perin\",\"number\":37}\n"
+        );
+        Ok(())
+    }
+
+    // 测试选择不存在的列时返回错误
+    #[test]
+    fn test_convert_csv_select_missing_column() {
+        let config = CsvConvertConfig {
+            select: vec!["age".to_string()],
+            ..Default::default()
+        };
+
+        let result = convert_csv(
+            "./fixtures/process_csv/test.csv".to_string(),
+            CsvFormatType::Json,
+            &config,
+        );
+        assert!(result.is_err());
+    }
+
+    // 测试过滤表达式中的列名拼写错误时返回错误，而不是按字符串比较
+    #[test]
+    fn test_convert_csv_filter_missing_column() {
+        for filter in ["Natonality==Italy", "age>3", "!retired"] {
+            let config = CsvConvertConfig {
+                filter: Some(filter.to_string()),
+                ..Default::default()
+            };
+
+            let result = convert_csv(
+                "./fixtures/process_csv/test.csv".to_string(),
+                CsvFormatType::Json,
+                &config,
+            );
+            let err = result.unwrap_err().to_string();
+            assert!(err.starts_with("列不存在"), "{}: {}", filter, err);
+        }
+    }
+
+    // 测试GBK编码的输入自动识别，并以UTF-16输出
+    #[test]
+    fn test_convert_csv_encoding() -> Result<()> {
+        let (input, _, _) = encoding_rs::GBK.encode("姓名,城市\n张三,北京\n");
+        let config = CsvConvertConfig {
+            dialect: CsvDialect {
+                output_encoding: CsvEncoding::Utf16Le,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut output = Vec::new();
+        convert_csv_stream(
+            &input[..],
+            &mut output,
+            CsvFormatType::Ndjson,
+            &config,
+            None,
+        )?;
+
+        let expected = "{\"姓名\":\"张三\",\"城市\":\"北京\"}\n";
+        let (decoded, _, _) = encoding_rs::UTF_16LE.decode(&output);
+        assert_eq!(&output[..2], [0xff, 0xfe]);
+        assert_eq!(decoded, expected);
+
+        let config = CsvConvertConfig {
+            dialect: CsvDialect {
+                output_encoding: CsvEncoding::Gbk,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let result = convert_csv_stream(&input[..], Vec::new(), CsvFormatType::Cbor, &config, None);
+        assert!(result.is_err());
+        Ok(())
+    }
+}
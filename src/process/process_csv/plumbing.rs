@@ -0,0 +1,649 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use csv::{StringRecord, Writer};
+
+use crate::{
+    utils::{get_reader_from_path, get_writer_from_path},
+    CsvJoinKind,
+};
+
+use super::{
+    dialect::{build_append_writer, build_reader_with_delimiter, build_writer, read_headers},
+    encoding::EncodingWriter,
+    CsvDialect,
+};
+
+// 按列值拆分时最多同时打开的输出文件数，超出时关闭最久未写入的文件
+const MAX_OPEN_WRITERS: usize = 256;
+
+// 拆分csv的方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvSplitBy {
+    // 每个文件的最大行数
+    Rows(usize),
+    // 按该列的值拆分，值相同的行写入同一个文件
+    Column(String),
+}
+
+fn column_index(headers: &StringRecord, column: &str) -> Result<usize> {
+    headers
+        .iter()
+        .position(|header| header == column)
+        .ok_or_else(|| anyhow!("列不存在: {}", column))
+}
+
+fn record_key(record: &StringRecord, indexes: &[usize]) -> Vec<String> {
+    indexes
+        .iter()
+        .map(|&i| record.get(i).unwrap_or_default().to_string())
+        .collect()
+}
+
+// 按键列连接两个csv，右侧文件读入内存，左侧文件逐行处理，返回输出的行数
+// 输出的列为左侧的全部列及右侧的非键列，右侧列名与左侧重复时追加 _right
+pub fn join_csv<R1: Read, R2: Read, W: Write>(
+    left: R1,
+    right: R2,
+    output: W,
+    key_columns: &[String],
+    kind: CsvJoinKind,
+    dialect: &CsvDialect,
+) -> Result<usize> {
+    if key_columns.is_empty() {
+        return Err(anyhow!("至少需要指定一个键列"));
+    }
+    let (mut left, delimiter) = build_reader_with_delimiter(left, dialect)?;
+    let left_headers = read_headers(&mut left, dialect)?;
+    let (mut right, _) = build_reader_with_delimiter(right, dialect)?;
+    let right_headers = read_headers(&mut right, dialect)?;
+
+    let left_keys = key_columns
+        .iter()
+        .map(|column| column_index(&left_headers, column))
+        .collect::<Result<Vec<_>>>()?;
+    let right_keys = key_columns
+        .iter()
+        .map(|column| column_index(&right_headers, column))
+        .collect::<Result<Vec<_>>>()?;
+    let right_values: Vec<usize> = (0..right_headers.len())
+        .filter(|i| !right_keys.contains(i))
+        .collect();
+
+    let mut right_rows = Vec::new();
+    let mut right_index: HashMap<Vec<String>, Vec<usize>> = HashMap::new();
+    for record in right.records() {
+        let record = record?;
+        right_index
+            .entry(record_key(&record, &right_keys))
+            .or_default()
+            .push(right_rows.len());
+        right_rows.push(record);
+    }
+    let mut matched = vec![false; right_rows.len()];
+
+    let mut writer = build_writer(output, dialect, delimiter);
+    if dialect.has_headers {
+        let right_names = right_values.iter().map(|&i| {
+            let name = &right_headers[i];
+            if left_headers.iter().any(|header| header == name) {
+                format!("{}_right", name)
+            } else {
+                name.to_string()
+            }
+        });
+        let headers: Vec<String> = left_headers
+            .iter()
+            .map(String::from)
+            .chain(right_names)
+            .collect();
+        writer.write_record(&headers)?;
+    }
+
+    let empty_right = vec![""; right_values.len()];
+    let mut count = 0;
+    for record in left.records() {
+        let record = record?;
+        let key = record_key(&record, &left_keys);
+        match right_index.get(&key) {
+            Some(indexes) => {
+                for &index in indexes {
+                    matched[index] = true;
+                    let right = &right_rows[index];
+                    let values = right_values
+                        .iter()
+                        .map(|&i| right.get(i).unwrap_or_default());
+                    writer.write_record(record.iter().chain(values))?;
+                    count += 1;
+                }
+            }
+            None if kind == CsvJoinKind::Inner => {}
+            None => {
+                writer.write_record(record.iter().chain(empty_right.iter().copied()))?;
+                count += 1;
+            }
+        }
+    }
+
+    // 全连接时输出右侧未匹配的行，左侧只填充键列
+    if kind == CsvJoinKind::Full {
+        for (right, _) in right_rows.iter().zip(&matched).filter(|(_, &m)| !m) {
+            let mut fields = vec![""; left_headers.len()];
+            for (&left_index, &right_index) in left_keys.iter().zip(&right_keys) {
+                fields[left_index] = right.get(right_index).unwrap_or_default();
+            }
+            let values = right_values
+                .iter()
+                .map(|&i| right.get(i).unwrap_or_default());
+            writer.write_record(fields.into_iter().chain(values))?;
+            count += 1;
+        }
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+// 合并多个csv，输出的列为所有文件表头的并集（按首次出现的顺序），缺少的列为空，返回输出的行数
+// 输出使用第一个文件的分隔符
+pub fn cat_csv<R: Read, W: Write>(
+    inputs: Vec<R>,
+    output: W,
+    dialect: &CsvDialect,
+) -> Result<usize> {
+    let mut readers = Vec::with_capacity(inputs.len());
+    let mut columns: Vec<String> = Vec::new();
+    let mut output_delimiter = None;
+    for input in inputs {
+        let (mut reader, delimiter) = build_reader_with_delimiter(input, dialect)?;
+        output_delimiter.get_or_insert(delimiter);
+        let headers = read_headers(&mut reader, dialect)?;
+        for header in headers.iter() {
+            if !columns.iter().any(|column| column == header) {
+                columns.push(header.to_string());
+            }
+        }
+        readers.push((reader, headers));
+    }
+
+    let delimiter = output_delimiter.unwrap_or(b',');
+    let mut writer = build_writer(output, dialect, delimiter);
+    if dialect.has_headers {
+        writer.write_record(&columns)?;
+    }
+    let mut count = 0;
+    for (mut reader, headers) in readers {
+        // 输出列在当前文件中的位置
+        let positions: Vec<Option<usize>> = columns
+            .iter()
+            .map(|column| headers.iter().position(|header| header == column))
+            .collect();
+        for record in reader.records() {
+            let record = record?;
+            writer.write_record(
+                positions
+                    .iter()
+                    .map(|position| position.and_then(|i| record.get(i)).unwrap_or_default()),
+            )?;
+            count += 1;
+        }
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+// 将列值转换为合法的文件名，空值使用 empty
+fn file_name_part(value: &str) -> String {
+    if value.is_empty() {
+        return "empty".to_string();
+    }
+    value
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// 按行数或列值拆分csv，每个文件都包含表头，返回生成的文件路径
+// 按列值拆分时转换为文件名后相同的不同值追加序号区分，不覆盖已存在的文件，出错时删除已生成的文件
+pub fn split_csv<R: Read>(
+    input: R,
+    out_dir: &Path,
+    prefix: &str,
+    by: &CsvSplitBy,
+    dialect: &CsvDialect,
+) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    match write_split(input, out_dir, prefix, by, dialect, &mut paths) {
+        Ok(()) => Ok(paths),
+        Err(e) => {
+            for path in &paths {
+                let _ = fs::remove_file(path);
+            }
+            Err(e)
+        }
+    }
+}
+
+fn write_split<R: Read>(
+    input: R,
+    out_dir: &Path,
+    prefix: &str,
+    by: &CsvSplitBy,
+    dialect: &CsvDialect,
+    paths: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let (mut reader, delimiter) = build_reader_with_delimiter(input, dialect)?;
+    let headers = read_headers(&mut reader, dialect)?;
+    let column = match by {
+        CsvSplitBy::Rows(0) => return Err(anyhow!("每个文件的行数必须大于0")),
+        CsvSplitBy::Rows(_) => None,
+        CsvSplitBy::Column(column) => Some(column_index(&headers, column)?),
+    };
+    fs::create_dir_all(out_dir)?;
+
+    // 已创建的文件名，被关闭的文件再次写入时以追加方式打开
+    let mut created = HashSet::new();
+    // 列值对应的文件名
+    let mut names: HashMap<String, String> = HashMap::new();
+    // 打开的文件及最后写入的序号
+    let mut writers: HashMap<String, (Writer<EncodingWriter<fs::File>>, usize)> = HashMap::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record?;
+        let name = match (by, column) {
+            (CsvSplitBy::Rows(rows), _) => format!("{}_{}.csv", prefix, i / rows + 1),
+            (_, Some(column)) => {
+                let value = record.get(column).unwrap_or_default();
+                match names.get(value) {
+                    Some(name) => name.clone(),
+                    None => {
+                        let part = file_name_part(value);
+                        let mut name = format!("{}_{}.csv", prefix, part);
+                        let mut index = 2;
+                        while created.contains(&name) {
+                            name = format!("{}_{}_{}.csv", prefix, part, index);
+                            index += 1;
+                        }
+                        names.insert(value.to_string(), name.clone());
+                        name
+                    }
+                }
+            }
+            _ => unreachable!(),
+        };
+        if !writers.contains_key(&name) {
+            // 按行数拆分时之前的文件已写完
+            if matches!(by, CsvSplitBy::Rows(_)) {
+                for (_, (mut writer, _)) in writers.drain() {
+                    writer.flush()?;
+                }
+            }
+            if writers.len() >= MAX_OPEN_WRITERS {
+                let oldest = writers
+                    .iter()
+                    .min_by_key(|(_, (_, last))| *last)
+                    .map(|(name, _)| name.clone());
+                if let Some((mut writer, _)) = oldest.and_then(|name| writers.remove(&name)) {
+                    writer.flush()?;
+                }
+            }
+            let path = out_dir.join(&name);
+            let writer = if created.contains(&name) {
+                build_append_writer(
+                    fs::OpenOptions::new().append(true).open(&path)?,
+                    dialect,
+                    delimiter,
+                )
+            } else {
+                let file = fs::OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&path)
+                    .map_err(|e| match e.kind() {
+                        io::ErrorKind::AlreadyExists => {
+                            anyhow!("输出文件已存在: {}", path.display())
+                        }
+                        _ => e.into(),
+                    })?;
+                let mut writer = build_writer(file, dialect, delimiter);
+                paths.push(path);
+                created.insert(name.clone());
+                if dialect.has_headers {
+                    writer.write_record(&headers)?;
+                }
+                writer
+            };
+            writers.insert(name.clone(), (writer, i));
+        }
+        if let Some((writer, last)) = writers.get_mut(&name) {
+            writer.write_record(&record)?;
+            *last = i;
+        }
+    }
+    for (_, (mut writer, _)) in writers {
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+// 去除重复的行，保留第一次出现的行，未指定键列时比较整行，返回保留和删除的行数
+pub fn dedup_csv<R: Read, W: Write>(
+    input: R,
+    output: W,
+    key_columns: &[String],
+    dialect: &CsvDialect,
+) -> Result<(usize, usize)> {
+    let (mut reader, delimiter) = build_reader_with_delimiter(input, dialect)?;
+    let headers = read_headers(&mut reader, dialect)?;
+    let keys = key_columns
+        .iter()
+        .map(|column| column_index(&headers, column))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut writer = build_writer(output, dialect, delimiter);
+    if dialect.has_headers {
+        writer.write_record(&headers)?;
+    }
+    let mut seen = HashSet::new();
+    let (mut kept, mut removed) = (0, 0);
+    for record in reader.records() {
+        let record = record?;
+        let key = if keys.is_empty() {
+            record.iter().map(String::from).collect()
+        } else {
+            record_key(&record, &keys)
+        };
+        if seen.insert(key) {
+            writer.write_record(&record)?;
+            kept += 1;
+        } else {
+            removed += 1;
+        }
+    }
+    writer.flush()?;
+    Ok((kept, removed))
+}
+
+// 连接两个csv文件
+pub fn join_csv_in_file(
+    left_path: &str,
+    right_path: &str,
+    save_path: &str,
+    key_columns: &[String],
+    kind: CsvJoinKind,
+    dialect: &CsvDialect,
+) -> Result<usize> {
+    join_csv(
+        get_reader_from_path(left_path)?,
+        get_reader_from_path(right_path)?,
+        get_writer_from_path(save_path)?,
+        key_columns,
+        kind,
+        dialect,
+    )
+}
+
+// 合并多个csv文件
+pub fn cat_csv_in_file(
+    input_paths: &[String],
+    save_path: &str,
+    dialect: &CsvDialect,
+) -> Result<usize> {
+    let inputs = input_paths
+        .iter()
+        .map(|path| get_reader_from_path(path))
+        .collect::<Result<Vec<_>>>()?;
+    cat_csv(inputs, get_writer_from_path(save_path)?, dialect)
+}
+
+// 拆分csv文件，未指定前缀时使用输入的文件名
+pub fn split_csv_in_file(
+    input_path: &str,
+    out_dir: &Path,
+    prefix: Option<&str>,
+    by: &CsvSplitBy,
+    dialect: &CsvDialect,
+) -> Result<Vec<PathBuf>> {
+    let prefix = prefix.unwrap_or_else(|| {
+        Path::new(input_path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .filter(|stem| *stem != "-")
+            .unwrap_or("part")
+    });
+    split_csv(
+        get_reader_from_path(input_path)?,
+        out_dir,
+        prefix,
+        by,
+        dialect,
+    )
+}
+
+// 去除csv文件中重复的行
+pub fn dedup_csv_in_file(
+    input_path: &str,
+    save_path: &str,
+    key_columns: &[String],
+    dialect: &CsvDialect,
+) -> Result<(usize, usize)> {
+    dedup_csv(
+        get_reader_from_path(input_path)?,
+        get_writer_from_path(save_path)?,
+        key_columns,
+        dialect,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::CsvEncoding;
+
+    const PLAYERS: &str = "id,name,team\n1,Tom,A\n2,Jerry,B\n3,Spike,C\n";
+    const TEAMS: &str = "team,name\nA,Alpha\nB,Beta\nB,Bravo\nD,Delta\n";
+
+    fn join(kind: CsvJoinKind) -> Result<String> {
+        let mut output = Vec::new();
+        join_csv(
+            PLAYERS.as_bytes(),
+            TEAMS.as_bytes(),
+            &mut output,
+            &["team".to_string()],
+            kind,
+            &CsvDialect::default(),
+        )?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    #[test]
+    fn test_join_csv() -> Result<()> {
+        assert_eq!(
+            join(CsvJoinKind::Inner)?,
+            "id,name,team,name_right\n1,Tom,A,Alpha\n2,Jerry,B,Beta\n2,Jerry,B,Bravo\n"
+        );
+        assert_eq!(
+            join(CsvJoinKind::Left)?,
+            "id,name,team,name_right\n1,Tom,A,Alpha\n2,Jerry,B,Beta\n2,Jerry,B,Bravo\n3,Spike,C,\n"
+        );
+        assert_eq!(
+            join(CsvJoinKind::Full)?,
+            "id,name,team,name_right\n1,Tom,A,Alpha\n2,Jerry,B,Beta\n2,Jerry,B,Bravo\n\
+             3,Spike,C,\n,,D,Delta\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cat_csv() -> Result<()> {
+        let mut output = Vec::new();
+        let count = cat_csv(
+            vec![
+                "id,name\n1,Tom\n".as_bytes(),
+                "name;age;id\nJerry;5;2\n".as_bytes(),
+            ],
+            &mut output,
+            &CsvDialect::default(),
+        )?;
+        assert_eq!(count, 2);
+        assert_eq!(
+            String::from_utf8(output)?,
+            "id,name,age\n1,Tom,\n2,Jerry,5\n"
+        );
+        Ok(())
+    }
+
+    // 测试合并后以gbk编码输出
+    #[test]
+    fn test_cat_csv_output_encoding() -> Result<()> {
+        let dialect = CsvDialect {
+            output_encoding: CsvEncoding::Gbk,
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        cat_csv(vec!["姓名\n张三\n".as_bytes()], &mut output, &dialect)?;
+        let (expected, _, _) = encoding_rs::GBK.encode("姓名\n张三\n");
+        assert_eq!(output, expected.as_ref());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_csv() -> Result<()> {
+        let input = "id,name\n1,Tom\n2,Jerry\n1,Tom\n1,Tommy\n";
+
+        let mut output = Vec::new();
+        let result = dedup_csv(input.as_bytes(), &mut output, &[], &CsvDialect::default())?;
+        assert_eq!(result, (3, 1));
+        assert_eq!(
+            String::from_utf8(output)?,
+            "id,name\n1,Tom\n2,Jerry\n1,Tommy\n"
+        );
+
+        let mut output = Vec::new();
+        let key = ["id".to_string()];
+        let result = dedup_csv(input.as_bytes(), &mut output, &key, &CsvDialect::default())?;
+        assert_eq!(result, (2, 2));
+        assert_eq!(String::from_utf8(output)?, "id,name\n1,Tom\n2,Jerry\n");
+        Ok(())
+    }
+
+    // 测试输出使用与输入相同的分隔符及引号
+    #[test]
+    fn test_dedup_csv_keep_dialect() -> Result<()> {
+        let mut output = Vec::new();
+        dedup_csv(
+            "id\tname\n1\tTom\n1\tTom\n2\t'a\tb'\n".as_bytes(),
+            &mut output,
+            &[],
+            &CsvDialect {
+                quote: b'\'',
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(String::from_utf8(output)?, "id\tname\n1\tTom\n2\t'a\tb'\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_csv() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rrcli_split_{}", std::process::id()));
+        let dialect = CsvDialect::default();
+
+        let paths = split_csv(
+            PLAYERS.as_bytes(),
+            &dir,
+            "rows",
+            &CsvSplitBy::Rows(2),
+            &dialect,
+        )?;
+        assert_eq!(paths, vec![dir.join("rows_1.csv"), dir.join("rows_2.csv")]);
+        assert_eq!(fs::read_to_string(&paths[1])?, "id,name,team\n3,Spike,C\n");
+
+        let by = CsvSplitBy::Column("team".to_string());
+        let paths = split_csv(TEAMS.as_bytes(), &dir, "teams", &by, &dialect)?;
+        assert_eq!(paths.len(), 3);
+        assert_eq!(
+            fs::read_to_string(dir.join("teams_B.csv"))?,
+            "team,name\nB,Beta\nB,Bravo\n"
+        );
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    // 测试不同的值超过同时打开的文件数时，关闭的文件以追加方式继续写入
+    #[test]
+    fn test_split_csv_many_values() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rrcli_split_many_{}", std::process::id()));
+        let dialect = CsvDialect {
+            output_encoding: CsvEncoding::Utf8Bom,
+            ..Default::default()
+        };
+        let values = MAX_OPEN_WRITERS + 44;
+        let mut input = String::from("id,value\n");
+        for round in 0..2 {
+            for value in 0..values {
+                input.push_str(&format!("{},{}\n", round, value));
+            }
+        }
+
+        let by = CsvSplitBy::Column("value".to_string());
+        let paths = split_csv(input.as_bytes(), &dir, "many", &by, &dialect)?;
+        assert_eq!(paths.len(), values);
+        assert_eq!(
+            fs::read(dir.join("many_0.csv"))?,
+            b"\xef\xbb\xbfid,value\n0,0\n1,0\n"
+        );
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    // 测试转换为文件名后相同的值写入不同的文件，已存在的文件不被覆盖或删除
+    #[test]
+    fn test_split_csv_name_collision() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rrcli_split_name_{}", std::process::id()));
+        let by = CsvSplitBy::Column("team".to_string());
+        let input = "team,name\na b,Tom\na/b,Jerry\na b,Spike\n";
+        let dialect = CsvDialect::default();
+
+        let paths = split_csv(input.as_bytes(), &dir, "t", &by, &dialect)?;
+        assert_eq!(paths, vec![dir.join("t_a_b.csv"), dir.join("t_a_b_2.csv")]);
+        assert_eq!(
+            fs::read_to_string(&paths[0])?,
+            "team,name\na b,Tom\na b,Spike\n"
+        );
+        assert_eq!(fs::read_to_string(&paths[1])?, "team,name\na/b,Jerry\n");
+
+        let err = split_csv(input.as_bytes(), &dir, "t", &by, &dialect).unwrap_err();
+        assert!(err.to_string().starts_with("输出文件已存在"));
+        assert_eq!(
+            fs::read_to_string(&paths[0])?,
+            "team,name\na b,Tom\na b,Spike\n"
+        );
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    // 测试出错时删除已生成的文件
+    #[test]
+    fn test_split_csv_error_cleanup() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rrcli_split_err_{}", std::process::id()));
+        let by = CsvSplitBy::Column("team".to_string());
+        let input = "team,name\nA,Alpha\nB,Beta\nC\n";
+        let result = split_csv(input.as_bytes(), &dir, "bad", &by, &CsvDialect::default());
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_dir(&dir)?.count(), 0);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
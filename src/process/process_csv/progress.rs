@@ -0,0 +1,60 @@
+use std::{
+    io::{stderr, Write},
+    time::{Duration, Instant},
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_millis(200);
+
+// 根据已读取的字节数在标准错误输出转换进度
+pub struct Progress {
+    total: Option<u64>,
+    last: Option<Instant>,
+}
+
+impl Progress {
+    pub fn new(total: Option<u64>) -> Self {
+        Self { total, last: None }
+    }
+
+    pub fn update(&mut self, bytes: u64) {
+        if self
+            .last
+            .is_some_and(|last| last.elapsed() < REFRESH_INTERVAL)
+        {
+            return;
+        }
+        self.last = Some(Instant::now());
+        self.print(bytes);
+    }
+
+    pub fn finish(&mut self, bytes: u64) {
+        self.print(bytes);
+        eprintln!();
+    }
+
+    fn print(&self, bytes: u64) {
+        let line = match self.total {
+            Some(total) if total > 0 => format!(
+                "\r转换进度: {} / {} ({:.1}%)",
+                format_bytes(bytes),
+                format_bytes(total),
+                bytes as f64 * 100.0 / total as f64
+            ),
+            _ => format!("\r已读取: {}", format_bytes(bytes)),
+        };
+        let mut stderr = stderr();
+        let _ = stderr.write_all(line.as_bytes());
+        let _ = stderr.flush();
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
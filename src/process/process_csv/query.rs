@@ -0,0 +1,369 @@
+use std::{
+    collections::HashSet,
+    io::{Read, Write},
+};
+
+use anyhow::{anyhow, Result};
+use rusqlite::{
+    types::{Value as SqlValue, ValueRef},
+    Connection,
+};
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    utils::{get_reader_from_path, get_writer_from_path},
+    CsvFormatType,
+};
+
+use super::{
+    dialect::{build_reader, column_name, read_headers},
+    record_to_map,
+    table::write_text_table,
+    writer::RecordWriter,
+    CsvConvertConfig,
+};
+
+// 查询使用的csv表，name为SQL中的表名
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvTable {
+    pub name: String,
+    pub path: String,
+}
+
+// 查询结果，列按SELECT中的顺序排列
+#[derive(Debug, Default, PartialEq)]
+pub struct CsvQueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+// 将csv加载到内存中的SQLite数据库后执行查询
+pub struct CsvQuery {
+    conn: Connection,
+    // 所有已加载表的列名，用于检查指定类型的列是否存在
+    columns: HashSet<String>,
+}
+
+// SQL标识符，双引号包裹，内部的双引号需双写
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn json_to_sql(value: Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => SqlValue::Text(s),
+        value => SqlValue::Text(value.to_string()),
+    }
+}
+
+fn sql_to_json(value: ValueRef) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::Number(i.into()),
+        ValueRef::Real(f) => Number::from_f64(f).map_or(Value::Null, Value::Number),
+        ValueRef::Text(s) | ValueRef::Blob(s) => {
+            Value::String(String::from_utf8_lossy(s).into_owned())
+        }
+    }
+}
+
+impl CsvQuery {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            conn: Connection::open_in_memory()?,
+            columns: HashSet::new(),
+        })
+    }
+
+    // 按方言读取csv并建表，字段类型与转换时的推断结果一致，返回加载的记录数
+    // 指定类型的列只作用于包含该列的表，加载全部表后再用 check_column_types 检查
+    pub fn load_table<R: Read>(
+        &mut self,
+        name: &str,
+        input: R,
+        config: &CsvConvertConfig,
+    ) -> Result<usize> {
+        let mut reader = build_reader(input, &config.dialect)?;
+        let headers = read_headers(&mut reader, &config.dialect)?;
+        self.columns.extend(headers.iter().map(String::from));
+
+        // 空列名使用生成的列名代替
+        let columns: Vec<String> = headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| match header.trim() {
+                "" => column_name(i + 1),
+                _ => header.to_string(),
+            })
+            .collect();
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            &format!(
+                "CREATE TABLE {} ({})",
+                quote_ident(name),
+                columns
+                    .iter()
+                    .map(|column| quote_ident(column))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            [],
+        )
+        .map_err(|e| anyhow!("创建表 \"{}\" 失败: {}", name, e))?;
+
+        let mut count = 0;
+        {
+            let placeholders = vec!["?"; columns.len()].join(", ");
+            let mut stmt = tx.prepare(&format!(
+                "INSERT INTO {} VALUES ({})",
+                quote_ident(name),
+                placeholders
+            ))?;
+            for record in reader.records() {
+                let mut map = record_to_map(&headers, &record?, config)?;
+                let values = headers
+                    .iter()
+                    .map(|header| json_to_sql(map.remove(header).unwrap_or_default()));
+                stmt.execute(rusqlite::params_from_iter(values))?;
+                count += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    // 检查指定类型的列至少存在于一个已加载的表中
+    pub fn check_column_types(&self, config: &CsvConvertConfig) -> Result<()> {
+        match config
+            .column_types
+            .keys()
+            .find(|column| !self.columns.contains(column.as_str()))
+        {
+            Some(column) => Err(anyhow!("指定类型的列在所有表中都不存在: {}", column)),
+            None => Ok(()),
+        }
+    }
+
+    // 执行单条只读的查询语句
+    pub fn execute(&self, sql: &str) -> Result<CsvQueryResult> {
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| anyhow!("SQL语句错误: {}", e))?;
+        if !stmt.readonly() {
+            return Err(anyhow!("只支持SELECT查询语句"));
+        }
+
+        let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+        let mut rows = Vec::new();
+        let mut result = stmt.query([])?;
+        while let Some(row) = result.next()? {
+            let values = (0..columns.len())
+                .map(|i| Ok(sql_to_json(row.get_ref(i)?)))
+                .collect::<Result<_>>()?;
+            rows.push(values);
+        }
+        Ok(CsvQueryResult { columns, rows })
+    }
+}
+
+impl CsvQueryResult {
+    // 转换为json对象，重名的列（如连接查询中的同名字段）追加序号区分
+    pub fn into_records(self) -> (Vec<String>, Vec<Value>) {
+        let mut columns: Vec<String> = Vec::with_capacity(self.columns.len());
+        for column in self.columns {
+            let mut name = column.clone();
+            let mut index = 2;
+            while columns.contains(&name) {
+                name = format!("{}_{}", column, index);
+                index += 1;
+            }
+            columns.push(name);
+        }
+        let records = self
+            .rows
+            .into_iter()
+            .map(|row| Value::Object(columns.iter().cloned().zip(row).collect::<Map<_, _>>()))
+            .collect();
+        (columns, records)
+    }
+
+    // 输出为终端表格，并在末尾输出行数
+    pub fn write_table(&self, writer: &mut impl Write) -> Result<()> {
+        write_text_table(writer, &self.columns, &self.rows)?;
+        writeln!(writer, "({} 行)", self.rows.len())?;
+        Ok(())
+    }
+}
+
+// 加载多个csv文件为表并执行查询，未指定输出格式时输出为终端表格
+pub fn query_csv_in_file(
+    tables: &[CsvTable],
+    sql: &str,
+    save_path: &str,
+    format_type: Option<CsvFormatType>,
+    config: &CsvConvertConfig,
+) -> Result<()> {
+    let mut query = CsvQuery::new()?;
+    for table in tables {
+        let input = get_reader_from_path(&table.path)?;
+        query
+            .load_table(&table.name, input, config)
+            .map_err(|e| anyhow!("加载 {} 失败: {}", table.path, e))?;
+    }
+    query.check_column_types(config)?;
+    let result = query.execute(sql)?;
+
+    let mut output = get_writer_from_path(save_path)?;
+    match format_type {
+        Some(format_type) => {
+            let (columns, records) = result.into_records();
+            let mut writer = RecordWriter::new(
+                output,
+                format_type,
+                config.toml_key.clone(),
+                config.toml_key_column.clone(),
+            )
+            .with_columns(columns);
+            for record in records {
+                writer.write(record)?;
+            }
+            writer.finish()?;
+        }
+        None => {
+            result.write_table(&mut output)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::CsvColumnType;
+    use serde_json::json;
+    use std::{collections::HashMap, fs};
+
+    fn players() -> Result<CsvQuery> {
+        let config = CsvConvertConfig {
+            infer_types: true,
+            ..Default::default()
+        };
+        let mut query = CsvQuery::new()?;
+        query.load_table("players", fs::File::open("assets/juventus.csv")?, &config)?;
+        query.load_table(
+            "countries",
+            "country;continent\nItaly;Europe\nBrazil;South America\n".as_bytes(),
+            &config,
+        )?;
+        Ok(query)
+    }
+
+    #[test]
+    fn test_query_group_by() -> Result<()> {
+        let result = players()?.execute(
+            "SELECT Nationality, count(*) AS total, max(\"Kit Number\") AS max_number \
+             FROM players WHERE Position != 'Goalkeeper' \
+             GROUP BY Nationality ORDER BY total DESC, Nationality LIMIT 2",
+        )?;
+
+        assert_eq!(result.columns, vec!["Nationality", "total", "max_number"]);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![json!("Italy"), json!(5), json!(33)],
+                vec![json!("Brazil"), json!(3), json!(13)],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_join() -> Result<()> {
+        let result = players()?.execute(
+            "SELECT c.continent, count(*) AS total FROM players p \
+             JOIN countries c ON p.Nationality = c.country \
+             GROUP BY c.continent ORDER BY c.continent",
+        )?;
+
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![json!("Europe"), json!(8)],
+                vec![json!("South America"), json!(3)],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_output() -> Result<()> {
+        let result = players()?.execute(
+            "SELECT p.Name, c.country AS Name, \"Kit Number\" FROM players p \
+             JOIN countries c ON p.Nationality = c.country \
+             ORDER BY \"Kit Number\" LIMIT 1",
+        )?;
+
+        let mut table = Vec::new();
+        result.write_table(&mut table)?;
+        assert_eq!(
+            String::from_utf8(table)?,
+            "+-------------------+-------+------------+\n\
+             | Name              | Name  | Kit Number |\n\
+             +-------------------+-------+------------+\n\
+             | Mattia De Sciglio | Italy |          2 |\n\
+             +-------------------+-------+------------+\n\
+             (1 行)\n"
+        );
+
+        let (columns, records) = result.into_records();
+        assert_eq!(columns, vec!["Name", "Name_2", "Kit Number"]);
+        assert_eq!(
+            records,
+            vec![json!({"Name": "Mattia De Sciglio", "Name_2": "Italy", "Kit Number": 2})]
+        );
+        Ok(())
+    }
+
+    // 测试指定类型的列只存在于部分表中
+    #[test]
+    fn test_query_join_column_types() -> Result<()> {
+        let config = CsvConvertConfig {
+            column_types: HashMap::from([("age".to_string(), CsvColumnType::Int)]),
+            ..Default::default()
+        };
+        let mut query = CsvQuery::new()?;
+        query.load_table("p", "id,age\n1,7\n2,31\n".as_bytes(), &config)?;
+        query.load_table("q", "id,team\n1,A\n2,B\n".as_bytes(), &config)?;
+        query.check_column_types(&config)?;
+        let result =
+            query.execute("SELECT q.team, p.age FROM p JOIN q ON p.id = q.id ORDER BY p.age")?;
+        assert_eq!(result.rows[0], vec![json!("A"), json!(7)]);
+
+        let config = CsvConvertConfig {
+            column_types: HashMap::from([("salary".to_string(), CsvColumnType::Int)]),
+            ..Default::default()
+        };
+        let err = query.check_column_types(&config).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("指定类型的列在所有表中都不存在"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_errors() -> Result<()> {
+        let query = players()?;
+        assert!(query.execute("DELETE FROM players").is_err());
+        assert!(query.execute("SELECT * FROM missing").is_err());
+        assert!(query.execute("SELECT 1; SELECT 2").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,734 @@
+use std::{
+    fmt::Display,
+    fs,
+    io::{Read, Write},
+};
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    utils::{get_reader_from_path, get_writer_from_path},
+    CsvColumnType,
+};
+
+use super::{
+    dialect::{build_reader, read_headers},
+    infer::{cast_value, infer_value},
+    stats::ColumnAccumulator,
+    CsvDialect,
+};
+
+const SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+// 推断schema的配置
+#[derive(Debug, Clone)]
+pub struct CsvSchemaConfig {
+    pub dialect: CsvDialect,
+    // 用于推断的最大行数
+    pub sample: usize,
+    // 不同值不超过该数量的字符串列输出为enum
+    pub max_enum: usize,
+}
+
+impl Default for CsvSchemaConfig {
+    fn default() -> Self {
+        Self {
+            dialect: CsvDialect::default(),
+            sample: 1000,
+            max_enum: 10,
+        }
+    }
+}
+
+// 字符串形状中的一段：连续的数字、连续的字母或其他字符
+#[derive(Debug, Clone, PartialEq)]
+enum ShapeKind {
+    Digit,
+    Letter,
+    Literal(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ShapePart {
+    kind: ShapeKind,
+    min: usize,
+    max: usize,
+}
+
+// 列中所有值的共同形状，如 Apr 18, 1990 与 Nov 1, 1992 均为 字母{3} 数字{1,2}, 数字{4}
+#[derive(Debug, Default)]
+enum Shape {
+    #[default]
+    Empty,
+    Consistent(Vec<ShapePart>),
+    Mixed,
+}
+
+fn shape_of(s: &str) -> Vec<ShapePart> {
+    let mut parts: Vec<ShapePart> = Vec::new();
+    for c in s.chars() {
+        let kind = if c.is_ascii_digit() {
+            ShapeKind::Digit
+        } else if c.is_alphabetic() {
+            ShapeKind::Letter
+        } else {
+            ShapeKind::Literal(c)
+        };
+        match parts.last_mut() {
+            Some(last) if last.kind == kind => {
+                last.min += 1;
+                last.max += 1;
+            }
+            _ => parts.push(ShapePart {
+                kind,
+                min: 1,
+                max: 1,
+            }),
+        }
+    }
+    parts
+}
+
+impl Shape {
+    fn add(&mut self, s: &str) {
+        let parts = shape_of(s);
+        *self = match std::mem::take(self) {
+            Shape::Empty => Shape::Consistent(parts),
+            Shape::Consistent(mut current) => {
+                let same = current.len() == parts.len()
+                    && current.iter().zip(&parts).all(|(a, b)| {
+                        a.kind == b.kind
+                            && (!matches!(a.kind, ShapeKind::Literal(_)) || a.min == b.min)
+                    });
+                if same {
+                    for (a, b) in current.iter_mut().zip(&parts) {
+                        a.min = a.min.min(b.min);
+                        a.max = a.max.max(b.max);
+                    }
+                    Shape::Consistent(current)
+                } else {
+                    Shape::Mixed
+                }
+            }
+            Shape::Mixed => Shape::Mixed,
+        };
+    }
+
+    // 只为包含数字的形状生成正则，避免对普通文本过度拟合
+    fn pattern(&self) -> Option<String> {
+        let Shape::Consistent(parts) = self else {
+            return None;
+        };
+        if !parts.iter().any(|part| part.kind == ShapeKind::Digit) {
+            return None;
+        }
+        let mut pattern = String::from("^");
+        for part in parts {
+            let quantifier = if part.min == part.max {
+                format!("{{{}}}", part.min)
+            } else {
+                format!("{{{},{}}}", part.min, part.max)
+            };
+            match &part.kind {
+                ShapeKind::Digit => pattern.push_str(&format!("\\d{}", quantifier)),
+                ShapeKind::Letter => pattern.push_str(&format!("\\p{{L}}{}", quantifier)),
+                ShapeKind::Literal(c) => {
+                    pattern.push_str(&regex::escape(&c.to_string()).repeat(part.min))
+                }
+            }
+        }
+        pattern.push('$');
+        Some(pattern)
+    }
+}
+
+// 根据csv的前若干行推断JSON Schema：类型、必填列、低基数列的enum以及结构化字符串的pattern
+// 有空值的列类型中包含null且不作为必填列
+pub fn infer_csv_schema<R: Read>(input: R, config: &CsvSchemaConfig) -> Result<Value> {
+    let mut reader = build_reader(input, &config.dialect)?;
+    let headers = read_headers(&mut reader, &config.dialect)?;
+    let mut columns: Vec<(ColumnAccumulator, Shape)> =
+        headers.iter().map(|_| Default::default()).collect();
+
+    for record in reader.records().take(config.sample) {
+        let record = record?;
+        for (i, (column, shape)) in columns.iter_mut().enumerate() {
+            let field = record.get(i).unwrap_or_default();
+            column.add(field);
+            if !field.is_empty() {
+                shape.add(field);
+            }
+        }
+    }
+
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for (name, (column, shape)) in headers.iter().zip(columns) {
+        let column_type = column.column_type();
+        let mut property = Map::new();
+        if column_type != "null" {
+            property.insert(
+                "type".to_string(),
+                if column.nulls > 0 {
+                    json!([column_type, "null"])
+                } else {
+                    json!(column_type)
+                },
+            );
+            if column.nulls == 0 {
+                required.push(Value::from(name));
+            }
+        }
+
+        if column_type == "string" {
+            let distinct = column.frequencies.len();
+            let total: usize = column.frequencies.values().sum();
+            // 值需要有重复才视为枚举，避免把少量样本中的唯一值当作枚举
+            if !column.distinct_truncated && distinct <= config.max_enum && distinct * 2 <= total {
+                let mut values: Vec<&str> = column.frequencies.keys().map(String::as_str).collect();
+                values.sort();
+                let mut values: Vec<Value> = values.into_iter().map(Value::from).collect();
+                if column.nulls > 0 {
+                    values.push(Value::Null);
+                }
+                property.insert("enum".to_string(), Value::Array(values));
+            } else if let Some(pattern) = shape.pattern() {
+                property.insert("pattern".to_string(), Value::String(pattern));
+            }
+        }
+        properties.insert(name.to_string(), Value::Object(property));
+    }
+
+    Ok(json!({
+        "$schema": SCHEMA_DRAFT,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    }))
+}
+
+// 校验失败的位置及违反的约束
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvValidationError {
+    pub line: u64,
+    pub column: String,
+    pub keyword: &'static str,
+    pub message: String,
+}
+
+impl Display for CsvValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "第{}行 \"{}\" 列违反 {} 约束: {}",
+            self.line, self.column, self.keyword, self.message
+        )
+    }
+}
+
+// 校验结果，rows为校验的记录数
+#[derive(Debug, Default)]
+pub struct CsvValidationReport {
+    pub rows: usize,
+    pub errors: Vec<CsvValidationError>,
+}
+
+// 单列的约束，支持 type、enum、const、pattern、minLength、maxLength、
+// minimum、maximum、exclusiveMinimum、exclusiveMaximum，使用其他校验关键字时报错
+#[derive(Debug, Default)]
+struct ColumnRule {
+    types: Vec<String>,
+    enum_values: Option<Vec<Value>>,
+    const_value: Option<Value>,
+    pattern: Option<Regex>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    exclusive_minimum: Option<f64>,
+    exclusive_maximum: Option<f64>,
+}
+
+// 不影响校验结果的注释类关键字
+const ANNOTATION_KEYWORDS: [&str; 9] = [
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+];
+
+const COLUMN_KEYWORDS: [&str; 10] = [
+    "type",
+    "enum",
+    "const",
+    "pattern",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+];
+
+const ROOT_KEYWORDS: [&str; 4] = ["type", "properties", "required", "additionalProperties"];
+
+// 检查schema中是否使用了不支持的关键字，避免被忽略的约束让校验误报通过
+fn check_keywords(schema: &Value, supported: &[&str], location: &str) -> Result<()> {
+    let Some(schema) = schema.as_object() else {
+        return Err(anyhow!("{}必须为对象", location));
+    };
+    let unsupported: Vec<&str> = schema
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !supported.contains(key) && !ANNOTATION_KEYWORDS.contains(key))
+        .collect();
+    if unsupported.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "{}使用了不支持的关键字: {}",
+            location,
+            unsupported.join(", ")
+        ))
+    }
+}
+
+// 尝试类型的顺序，字符串放在最后以便数字优先按数值校验
+const TYPE_ORDER: [(&str, Option<CsvColumnType>); 4] = [
+    ("integer", Some(CsvColumnType::Int)),
+    ("number", Some(CsvColumnType::Float)),
+    ("boolean", Some(CsvColumnType::Bool)),
+    ("string", None),
+];
+
+impl ColumnRule {
+    fn compile(name: &str, schema: &Value) -> Result<Self> {
+        check_keywords(
+            schema,
+            &COLUMN_KEYWORDS,
+            &format!("列 \"{}\" 的schema", name),
+        )?;
+        let number = |key: &str| schema.get(key).and_then(Value::as_f64);
+        let length = |key: &str| schema.get(key).and_then(Value::as_u64).map(|n| n as usize);
+        let types = match schema.get("type") {
+            None => Vec::new(),
+            Some(Value::String(t)) => vec![t.clone()],
+            Some(Value::Array(types)) => types
+                .iter()
+                .map(|t| {
+                    t.as_str()
+                        .map(String::from)
+                        .ok_or_else(|| anyhow!("列 \"{}\" 的type格式错误", name))
+                })
+                .collect::<Result<_>>()?,
+            Some(_) => return Err(anyhow!("列 \"{}\" 的type格式错误", name)),
+        };
+        let pattern = schema
+            .get("pattern")
+            .and_then(Value::as_str)
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| anyhow!("列 \"{}\" 的pattern无效: {}", name, e))?;
+
+        Ok(Self {
+            types,
+            enum_values: schema.get("enum").and_then(Value::as_array).cloned(),
+            const_value: schema.get("const").cloned(),
+            pattern,
+            min_length: length("minLength"),
+            max_length: length("maxLength"),
+            minimum: number("minimum"),
+            maximum: number("maximum"),
+            exclusive_minimum: number("exclusiveMinimum"),
+            exclusive_maximum: number("exclusiveMaximum"),
+        })
+    }
+
+    fn allows(&self, column_type: &str) -> bool {
+        self.types.is_empty() || self.types.iter().any(|t| t == column_type)
+    }
+
+    // 按schema中的类型转换字段，无法转换为任何类型时返回None
+    fn typed_value(&self, field: &str) -> Option<Value> {
+        if self.types.is_empty() {
+            return Some(infer_value(field));
+        }
+        TYPE_ORDER
+            .iter()
+            .filter(|(name, _)| self.allows(name))
+            .find_map(|(_, column_type)| match column_type {
+                Some(column_type) => cast_value(field, *column_type).ok(),
+                None => Some(Value::String(field.to_string())),
+            })
+    }
+
+    // 校验单个字段，返回违反的约束及说明
+    fn check(&self, field: &str, required: bool) -> Vec<(&'static str, String)> {
+        let mut errors = Vec::new();
+        if field.is_empty() {
+            // 未限制类型或允许null时空值合法
+            if self.allows("null") {
+                return errors;
+            }
+            if required {
+                errors.push(("required", "值为空".to_string()));
+            } else {
+                errors.push(("type", format!("空值不是 {} 类型", self.types.join("/"))));
+            }
+            return errors;
+        }
+
+        let Some(value) = self.typed_value(field) else {
+            errors.push((
+                "type",
+                format!("\"{}\" 不是 {} 类型", field, self.types.join("/")),
+            ));
+            return errors;
+        };
+
+        // 按转换后的值严格比较，只有数值之间忽略整数与浮点数的区别，"007" 不等于 7
+        let equals = |expected: &Value| match (value.as_f64(), expected.as_f64()) {
+            (Some(l), Some(r)) => l == r,
+            _ => &value == expected,
+        };
+        if let Some(values) = &self.enum_values {
+            if !values.iter().any(equals) {
+                errors.push(("enum", format!("\"{}\" 不在可选值中", field)));
+            }
+        }
+        if let Some(expected) = &self.const_value {
+            if !equals(expected) {
+                errors.push(("const", format!("\"{}\" 不等于 {}", field, expected)));
+            }
+        }
+
+        // 字符串约束作用于字段的原始内容，未指定类型时推断为数值的字段也要检查
+        let length = field.chars().count();
+        if let Some(pattern) = &self.pattern {
+            if !pattern.is_match(field) {
+                errors.push((
+                    "pattern",
+                    format!("\"{}\" 不匹配 {}", field, pattern.as_str()),
+                ));
+            }
+        }
+        if self.min_length.is_some_and(|min| length < min) {
+            errors.push((
+                "minLength",
+                format!(
+                    "\"{}\" 的长度小于 {}",
+                    field,
+                    self.min_length.unwrap_or_default()
+                ),
+            ));
+        }
+        if self.max_length.is_some_and(|max| length > max) {
+            errors.push((
+                "maxLength",
+                format!(
+                    "\"{}\" 的长度大于 {}",
+                    field,
+                    self.max_length.unwrap_or_default()
+                ),
+            ));
+        }
+
+        if let Some(n) = value.as_f64() {
+            let checks = [
+                (
+                    "minimum",
+                    self.minimum,
+                    n < self.minimum.unwrap_or(f64::MIN),
+                    "小于",
+                ),
+                (
+                    "maximum",
+                    self.maximum,
+                    n > self.maximum.unwrap_or(f64::MAX),
+                    "大于",
+                ),
+                (
+                    "exclusiveMinimum",
+                    self.exclusive_minimum,
+                    n <= self.exclusive_minimum.unwrap_or(f64::MIN),
+                    "不大于",
+                ),
+                (
+                    "exclusiveMaximum",
+                    self.exclusive_maximum,
+                    n >= self.exclusive_maximum.unwrap_or(f64::MAX),
+                    "不小于",
+                ),
+            ];
+            for (keyword, limit, failed, text) in checks {
+                if let Some(limit) = limit.filter(|_| failed) {
+                    errors.push((keyword, format!("{} {} {}", field, text, limit)));
+                }
+            }
+        }
+        errors
+    }
+}
+
+// 按JSON Schema逐行校验csv，列缺失或多余时在表头所在的第1行报告
+pub fn validate_csv<R: Read>(
+    input: R,
+    schema: &Value,
+    dialect: &CsvDialect,
+) -> Result<CsvValidationReport> {
+    check_keywords(schema, &ROOT_KEYWORDS, "schema")?;
+    if !matches!(
+        schema.get("additionalProperties"),
+        None | Some(Value::Bool(_))
+    ) {
+        return Err(anyhow!("schema中的additionalProperties只支持true或false"));
+    }
+    let properties = match schema.get("properties") {
+        Some(Value::Object(properties)) => properties.clone(),
+        Some(_) => return Err(anyhow!("schema中的properties必须为对象")),
+        None => Map::new(),
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let additional = schema.get("additionalProperties") != Some(&Value::Bool(false));
+
+    let mut reader = build_reader(input, dialect)?;
+    let headers = read_headers(&mut reader, dialect)?;
+    let mut report = CsvValidationReport::default();
+
+    for column in &required {
+        if !headers.iter().any(|header| header == *column) {
+            report.errors.push(CsvValidationError {
+                line: 1,
+                column: column.to_string(),
+                keyword: "required",
+                message: "缺少该列".to_string(),
+            });
+        }
+    }
+    let mut rules = Vec::with_capacity(headers.len());
+    for header in headers.iter() {
+        let rule = match properties.get(header) {
+            Some(property) => Some(ColumnRule::compile(header, property)?),
+            None if additional => None,
+            None => {
+                report.errors.push(CsvValidationError {
+                    line: 1,
+                    column: header.to_string(),
+                    keyword: "additionalProperties",
+                    message: "schema中不存在该列".to_string(),
+                });
+                None
+            }
+        };
+        rules.push(rule.map(|rule| (rule, required.contains(&header))));
+    }
+
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        for (i, (header, rule)) in headers.iter().zip(&rules).enumerate() {
+            let Some((rule, required)) = rule else {
+                continue;
+            };
+            for (keyword, message) in rule.check(record.get(i).unwrap_or_default(), *required) {
+                report.errors.push(CsvValidationError {
+                    line,
+                    column: header.to_string(),
+                    keyword,
+                    message,
+                });
+            }
+        }
+        report.rows += 1;
+    }
+    Ok(report)
+}
+
+// 推断csv文件的schema并写出
+pub fn schema_csv_in_file(
+    input_path: &str,
+    save_path: &str,
+    config: &CsvSchemaConfig,
+) -> Result<()> {
+    let schema = infer_csv_schema(get_reader_from_path(input_path)?, config)?;
+    let mut output = get_writer_from_path(save_path)?;
+    serde_json::to_writer_pretty(&mut output, &schema)?;
+    writeln!(output)?;
+    output.flush()?;
+    Ok(())
+}
+
+// 使用schema文件校验csv文件
+pub fn validate_csv_in_file(
+    input_path: &str,
+    schema_path: &str,
+    dialect: &CsvDialect,
+) -> Result<CsvValidationReport> {
+    let schema: Value = serde_json::from_str(&fs::read_to_string(schema_path)?)
+        .map_err(|e| anyhow!("schema文件格式错误: {}", e))?;
+    validate_csv(get_reader_from_path(input_path)?, &schema, dialect)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_infer_csv_schema() -> Result<()> {
+        let schema = infer_csv_schema(
+            fs::File::open("assets/juventus.csv")?,
+            &CsvSchemaConfig::default(),
+        )?;
+
+        let properties = &schema["properties"];
+        assert_eq!(properties["Kit Number"], json!({"type": "integer"}));
+        assert_eq!(properties["Name"], json!({"type": "string"}));
+        assert_eq!(
+            properties["DOB"]["pattern"],
+            json!("^\\p{L}{3} \\d{1,2}, \\d{4} \\(\\d{2}\\)$")
+        );
+        assert_eq!(properties["Position"]["enum"].as_array().unwrap().len(), 10);
+        assert_eq!(
+            schema["required"],
+            json!(["Name", "Position", "DOB", "Nationality", "Kit Number"])
+        );
+
+        // 推断的schema应能通过同一份数据的校验
+        let report = validate_csv(
+            fs::File::open("assets/juventus.csv")?,
+            &schema,
+            &CsvDialect::default(),
+        )?;
+        assert_eq!(report.rows, 27);
+        assert_eq!(report.errors, vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_infer_csv_schema_nullable() -> Result<()> {
+        let input = "code,level,note\n001,low,\n002,high,\n003,low,x\n004,,y\n005,high,\n";
+        let schema = infer_csv_schema(input.as_bytes(), &CsvSchemaConfig::default())?;
+
+        assert_eq!(
+            schema["properties"],
+            json!({
+                "code": {"type": "string", "pattern": "^\\d{3}$"},
+                "level": {"type": ["string", "null"], "enum": ["high", "low", null]},
+                "note": {"type": ["string", "null"]},
+            })
+        );
+        assert_eq!(schema["required"], json!(["code"]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_csv() -> Result<()> {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "minLength": 2},
+                "age": {"type": "integer", "minimum": 18},
+                "level": {"enum": ["low", "high"]},
+                "zip": {"type": ["string", "null"], "pattern": "^\\d{5}$"}
+            },
+            "required": ["name", "age", "email"],
+            "additionalProperties": false
+        });
+        let input = "name,age,level,zip,extra\n\
+                     Tom,20,low,10100,x\n\
+                     J,17,mid,,\n\
+                     ,abc,high,1010,\n";
+        let report = validate_csv(input.as_bytes(), &schema, &CsvDialect::default())?;
+
+        let errors: Vec<(u64, &str, &str)> = report
+            .errors
+            .iter()
+            .map(|e| (e.line, e.column.as_str(), e.keyword))
+            .collect();
+        assert_eq!(report.rows, 3);
+        assert_eq!(
+            errors,
+            vec![
+                (1, "email", "required"),
+                (1, "extra", "additionalProperties"),
+                (3, "name", "minLength"),
+                (3, "age", "minimum"),
+                (3, "level", "enum"),
+                (4, "name", "required"),
+                (4, "age", "type"),
+                (4, "zip", "pattern"),
+            ]
+        );
+        assert_eq!(
+            report.errors[3].to_string(),
+            "第3行 \"age\" 列违反 minimum 约束: 17 小于 18"
+        );
+        Ok(())
+    }
+
+    // 测试enum、const严格比较，字符串约束作用于推断为数值的字段
+    #[test]
+    fn test_validate_csv_strict_values() -> Result<()> {
+        let schema = json!({
+            "properties": {
+                "code": {"enum": [7]},
+                "level": {"type": "integer", "const": 3},
+                "name": {"pattern": "^[a-z]+$", "maxLength": 2}
+            }
+        });
+        let input = "code,level,name\n007,3.0,123\n7,03,ab\n";
+        let report = validate_csv(input.as_bytes(), &schema, &CsvDialect::default())?;
+
+        let errors: Vec<(u64, &str, &str)> = report
+            .errors
+            .iter()
+            .map(|e| (e.line, e.column.as_str(), e.keyword))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (2, "code", "enum"),
+                (2, "level", "type"),
+                (2, "name", "pattern"),
+                (2, "name", "maxLength"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_csv_unsupported_keyword() {
+        let input = "email,name\nnot-an-email,Tom\n";
+        for (schema, keyword) in [
+            (
+                json!({"properties": {"email": {"type": "string", "format": "email"}}}),
+                "format",
+            ),
+            (
+                json!({"properties": {}, "minProperties": 3}),
+                "minProperties",
+            ),
+            (
+                json!({"properties": {}, "additionalProperties": {"type": "string"}}),
+                "additionalProperties",
+            ),
+        ] {
+            let err = validate_csv(input.as_bytes(), &schema, &CsvDialect::default())
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains(keyword), "{}", err);
+        }
+    }
+}
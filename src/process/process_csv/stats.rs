@@ -0,0 +1,585 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+use anyhow::Result;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+use crate::utils::{get_reader_from_path, get_writer_from_path};
+
+use super::{
+    dialect::{build_reader, read_headers},
+    infer::infer_value,
+    table::write_text_table,
+    CsvDialect,
+};
+
+// 直方图中最长的条形宽度
+const HISTOGRAM_WIDTH: usize = 30;
+// 每列最多保留的数值样本数，超过时随机抽样估算中位数及分布
+const MAX_NUMBER_SAMPLES: usize = 100_000;
+// 每列最多统计的不同值个数，超过后不再统计新出现的值
+const MAX_DISTINCT: usize = 10_000;
+
+// csv统计配置
+#[derive(Debug, Clone)]
+pub struct CsvStatsConfig {
+    pub dialect: CsvDialect,
+    // 输出出现次数最多的前N个值
+    pub top: usize,
+    // 数值列直方图的分组数
+    pub bins: usize,
+}
+
+impl Default for CsvStatsConfig {
+    fn default() -> Self {
+        Self {
+            dialect: CsvDialect::default(),
+            top: 5,
+            bins: 10,
+        }
+    }
+}
+
+// csv文件的统计结果
+#[derive(Debug, Serialize)]
+pub struct CsvStats {
+    pub rows: usize,
+    pub columns: Vec<ColumnStats>,
+}
+
+// 单列的统计结果，数值统计只在整数、数字列中输出，长度统计只在字符串列中输出
+#[derive(Debug, Serialize)]
+pub struct ColumnStats {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub column_type: &'static str,
+    pub nulls: usize,
+    pub distinct: usize,
+    // 不同值超过上限时为true，此时distinct及常见值只包含先出现的值
+    pub distinct_truncated: bool,
+    pub min: Option<Value>,
+    pub max: Option<Value>,
+    pub mean: Option<f64>,
+    // 数值个数超过上限时根据随机抽样估算
+    pub median: Option<f64>,
+    // 样本标准差，少于两个值时为空
+    pub stddev: Option<f64>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub top: Vec<ValueCount>,
+    pub histogram: Vec<HistogramBin>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ValueCount {
+    pub value: String,
+    pub count: usize,
+}
+
+// 直方图分组，范围为 [start, end)，最后一组包含end，数值个数超过上限时按抽样比例估算个数
+#[derive(Debug, Serialize, PartialEq)]
+pub struct HistogramBin {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+}
+
+// 逐行累计单列的统计数据
+#[derive(Default)]
+pub(super) struct ColumnAccumulator {
+    pub(super) nulls: usize,
+    bools: usize,
+    ints: usize,
+    floats: usize,
+    strings: usize,
+    pub(super) frequencies: HashMap<String, usize>,
+    pub(super) distinct_truncated: bool,
+    // 整数的最小、最大值单独保存，避免大于2^53时丢失精度
+    int_range: Option<(i64, i64)>,
+    float_range: Option<(f64, f64)>,
+    // 数值的个数、平均值及与平均值之差的平方和（Welford算法）
+    count: usize,
+    mean: f64,
+    m2: f64,
+    // 数值的蓄水池抽样
+    samples: Vec<f64>,
+    rng: Option<StdRng>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+}
+
+impl ColumnAccumulator {
+    pub(super) fn add(&mut self, field: &str) {
+        let value = infer_value(field);
+        match &value {
+            Value::Null => {
+                self.nulls += 1;
+                return;
+            }
+            Value::Bool(_) => self.bools += 1,
+            Value::Number(n) => {
+                match n.as_i64() {
+                    Some(i) => {
+                        self.ints += 1;
+                        self.int_range = Some(
+                            self.int_range
+                                .map_or((i, i), |(min, max)| (min.min(i), max.max(i))),
+                        );
+                    }
+                    None => {
+                        self.floats += 1;
+                        let f = n.as_f64().unwrap_or_default();
+                        self.float_range = Some(
+                            self.float_range
+                                .map_or((f, f), |(min, max)| (min.min(f), max.max(f))),
+                        );
+                    }
+                }
+                self.add_number(n.as_f64().unwrap_or_default());
+            }
+            _ => self.strings += 1,
+        }
+        let length = field.chars().count();
+        self.min_length = Some(self.min_length.map_or(length, |min| min.min(length)));
+        self.max_length = Some(self.max_length.map_or(length, |max| max.max(length)));
+        let full = self.frequencies.len() >= MAX_DISTINCT;
+        match self.frequencies.get_mut(field) {
+            Some(count) => *count += 1,
+            None if full => self.distinct_truncated = true,
+            None => {
+                self.frequencies.insert(field.to_string(), 1);
+            }
+        }
+    }
+
+    fn add_number(&mut self, n: f64) {
+        self.count += 1;
+        let delta = n - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (n - self.mean);
+
+        if self.samples.len() < MAX_NUMBER_SAMPLES {
+            self.samples.push(n);
+            return;
+        }
+        // 固定种子，相同的输入得到相同的结果
+        let rng = self.rng.get_or_insert_with(|| StdRng::seed_from_u64(0));
+        let index = rng.gen_range(0..self.count);
+        if index < MAX_NUMBER_SAMPLES {
+            self.samples[index] = n;
+        }
+    }
+
+    // 所有值类型一致时为该类型，整数与浮点数混合时为number，其他混合情况为string
+    pub(super) fn column_type(&self) -> &'static str {
+        match (self.bools, self.ints, self.floats, self.strings) {
+            (0, 0, 0, 0) => "null",
+            (_, 0, 0, 0) => "boolean",
+            (0, _, 0, 0) => "integer",
+            (0, _, _, 0) => "number",
+            _ => "string",
+        }
+    }
+
+    fn finish(self, name: String, config: &CsvStatsConfig) -> ColumnStats {
+        let column_type = self.column_type();
+        let is_numeric = matches!(column_type, "integer" | "number");
+        let is_string = column_type == "string";
+
+        let mut top: Vec<ValueCount> = self
+            .frequencies
+            .iter()
+            .map(|(value, count)| ValueCount {
+                value: value.clone(),
+                count: *count,
+            })
+            .collect();
+        top.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        top.truncate(config.top);
+
+        let mut samples = if is_numeric { self.samples } else { Vec::new() };
+        samples.sort_by(f64::total_cmp);
+        let (min, max) = match (column_type, self.int_range, self.float_range) {
+            ("integer", Some((min, max)), _) => (Some(Value::from(min)), Some(Value::from(max))),
+            ("number", ints, floats) => {
+                let ints = ints.map(|(min, max)| (min as f64, max as f64));
+                let (min, max) = match (ints, floats) {
+                    (Some(a), Some(b)) => (a.0.min(b.0), a.1.max(b.1)),
+                    (Some(range), None) | (None, Some(range)) => range,
+                    (None, None) => (f64::NAN, f64::NAN),
+                };
+                let to_value = |n: f64| Number::from_f64(n).map(Value::Number);
+                (to_value(min), to_value(max))
+            }
+            _ => (None, None),
+        };
+        let count = if is_numeric { self.count } else { 0 };
+
+        ColumnStats {
+            name,
+            column_type,
+            nulls: self.nulls,
+            distinct: self.frequencies.len(),
+            distinct_truncated: self.distinct_truncated,
+            min,
+            max,
+            mean: (count > 0).then_some(self.mean),
+            median: median(&samples),
+            // 样本标准差
+            stddev: (count > 1).then(|| (self.m2 / (count - 1) as f64).sqrt()),
+            min_length: self.min_length.filter(|_| is_string),
+            max_length: self.max_length.filter(|_| is_string),
+            top,
+            histogram: histogram(&samples, count, config.bins),
+        }
+    }
+}
+
+// numbers需已排序
+fn median(numbers: &[f64]) -> Option<f64> {
+    let len = numbers.len();
+    match len {
+        0 => None,
+        _ if len % 2 == 1 => Some(numbers[len / 2]),
+        _ => Some((numbers[len / 2 - 1] + numbers[len / 2]) / 2.0),
+    }
+}
+
+// 在最小值与最大值之间等宽分组，numbers需已排序，为抽样时各组的个数按total的比例估算
+fn histogram(numbers: &[f64], total: usize, bins: usize) -> Vec<HistogramBin> {
+    let (Some(&min), Some(&max)) = (numbers.first(), numbers.last()) else {
+        return Vec::new();
+    };
+    if bins == 0 {
+        return Vec::new();
+    }
+    if min == max {
+        return vec![HistogramBin {
+            start: min,
+            end: max,
+            count: total,
+        }];
+    }
+
+    let width = (max - min) / bins as f64;
+    let mut result: Vec<HistogramBin> = (0..bins)
+        .map(|i| HistogramBin {
+            start: min + width * i as f64,
+            end: if i + 1 == bins {
+                max
+            } else {
+                min + width * (i + 1) as f64
+            },
+            count: 0,
+        })
+        .collect();
+    for n in numbers {
+        let index = (((n - min) / width) as usize).min(bins - 1);
+        result[index].count += 1;
+    }
+    if total > numbers.len() {
+        let scale = total as f64 / numbers.len() as f64;
+        for bin in &mut result {
+            bin.count = (bin.count as f64 * scale).round() as usize;
+        }
+    }
+    result
+}
+
+// 逐行读取csv并统计每一列
+pub fn collect_csv_stats<R: Read>(input: R, config: &CsvStatsConfig) -> Result<CsvStats> {
+    let mut reader = build_reader(input, &config.dialect)?;
+    let headers = read_headers(&mut reader, &config.dialect)?;
+    let mut columns: Vec<ColumnAccumulator> = headers
+        .iter()
+        .map(|_| ColumnAccumulator::default())
+        .collect();
+
+    let mut rows = 0;
+    for record in reader.records() {
+        let record = record?;
+        for (i, column) in columns.iter_mut().enumerate() {
+            column.add(record.get(i).unwrap_or_default());
+        }
+        rows += 1;
+    }
+
+    Ok(CsvStats {
+        rows,
+        columns: headers
+            .iter()
+            .zip(columns)
+            .map(|(name, column)| column.finish(name.to_string(), config))
+            .collect(),
+    })
+}
+
+// 终端显示时保留4位小数
+fn round_value(n: Option<f64>) -> Value {
+    n.and_then(|n| Number::from_f64((n * 10000.0).round() / 10000.0))
+        .map_or(Value::Null, Value::Number)
+}
+
+impl CsvStats {
+    // 输出为便于阅读的报告：汇总表格、每列的常见值及数值分布
+    pub fn write_report(&self, writer: &mut impl Write) -> Result<()> {
+        writeln!(writer, "行数: {}", self.rows)?;
+        let headers: Vec<String> = [
+            "column",
+            "type",
+            "nulls",
+            "distinct",
+            "min",
+            "max",
+            "mean",
+            "median",
+            "stddev",
+            "min_length",
+            "max_length",
+        ]
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+        let rows: Vec<Vec<Value>> = self
+            .columns
+            .iter()
+            .map(|column| {
+                [
+                    Value::from(column.name.clone()),
+                    Value::from(column.column_type),
+                    Value::from(column.nulls),
+                    if column.distinct_truncated {
+                        Value::from(format!("{}+", column.distinct))
+                    } else {
+                        Value::from(column.distinct)
+                    },
+                    column.min.clone().unwrap_or_default(),
+                    column.max.clone().unwrap_or_default(),
+                    round_value(column.mean),
+                    round_value(column.median),
+                    round_value(column.stddev),
+                    column.min_length.map_or(Value::Null, Value::from),
+                    column.max_length.map_or(Value::Null, Value::from),
+                ]
+                .into_iter()
+                // 不适用的统计项显示为空
+                .map(|value| match value {
+                    Value::Null => Value::String(String::new()),
+                    value => value,
+                })
+                .collect()
+            })
+            .collect();
+        write_text_table(writer, &headers, &rows)?;
+
+        for column in &self.columns {
+            if column.top.is_empty() {
+                continue;
+            }
+            writeln!(writer, "\n{} ({})", column.name, column.column_type)?;
+            writeln!(writer, "  常见值:")?;
+            let width = column
+                .top
+                .iter()
+                .map(|top| top.value.chars().count())
+                .max()
+                .unwrap_or_default();
+            for top in &column.top {
+                writeln!(
+                    writer,
+                    "    {:<width$}  {}",
+                    top.value,
+                    top.count,
+                    width = width
+                )?;
+            }
+
+            if column.histogram.is_empty() {
+                continue;
+            }
+            writeln!(writer, "  分布:")?;
+            let labels: Vec<String> = column
+                .histogram
+                .iter()
+                .map(|bin| {
+                    format!(
+                        "{} - {}",
+                        round_value(Some(bin.start)),
+                        round_value(Some(bin.end))
+                    )
+                })
+                .collect();
+            let width = labels
+                .iter()
+                .map(|label| label.len())
+                .max()
+                .unwrap_or_default();
+            let max_count = column
+                .histogram
+                .iter()
+                .map(|bin| bin.count)
+                .max()
+                .unwrap_or(1);
+            for (label, bin) in labels.iter().zip(&column.histogram) {
+                let bar = "#".repeat(bin.count * HISTOGRAM_WIDTH / max_count.max(1));
+                writeln!(
+                    writer,
+                    "    {:<width$}  {:<bar_width$}  {}",
+                    label,
+                    bar,
+                    bin.count,
+                    width = width,
+                    bar_width = HISTOGRAM_WIDTH
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// 统计csv文件的每一列，json为true时输出json，否则输出便于阅读的报告
+pub fn stats_csv_in_file(
+    input_path: &str,
+    save_path: &str,
+    config: &CsvStatsConfig,
+    json: bool,
+) -> Result<()> {
+    let stats = collect_csv_stats(get_reader_from_path(input_path)?, config)?;
+    let mut output = get_writer_from_path(save_path)?;
+    if json {
+        serde_json::to_writer_pretty(&mut output, &stats)?;
+        writeln!(output)?;
+    } else {
+        stats.write_report(&mut output)?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_collect_csv_stats() -> Result<()> {
+        let config = CsvStatsConfig {
+            top: 2,
+            bins: 3,
+            ..Default::default()
+        };
+        let input = "id,name,score,active\n1,x,1.5,true\n2,,2,false\n3,yy,,true\n4,x,abc,\n";
+        let stats = collect_csv_stats(input.as_bytes(), &config)?;
+        assert_eq!(stats.rows, 4);
+
+        let id = &stats.columns[0];
+        assert_eq!(id.column_type, "integer");
+        assert_eq!(
+            (id.min.clone(), id.max.clone()),
+            (Some(json!(1)), Some(json!(4)))
+        );
+        assert_eq!((id.mean, id.median), (Some(2.5), Some(2.5)));
+        assert!((id.stddev.unwrap() - 1.2910).abs() < 1e-4);
+        assert_eq!(
+            id.histogram.iter().map(|bin| bin.count).collect::<Vec<_>>(),
+            vec![1, 1, 2]
+        );
+        assert_eq!(id.min_length, None);
+
+        let name = &stats.columns[1];
+        assert_eq!(name.column_type, "string");
+        assert_eq!((name.nulls, name.distinct), (1, 2));
+        assert_eq!((name.min_length, name.max_length), (Some(1), Some(2)));
+        assert_eq!(
+            name.top,
+            vec![
+                ValueCount {
+                    value: "x".to_string(),
+                    count: 2
+                },
+                ValueCount {
+                    value: "yy".to_string(),
+                    count: 1
+                }
+            ]
+        );
+        assert!(name.histogram.is_empty());
+
+        assert_eq!(stats.columns[2].column_type, "string");
+        assert_eq!(stats.columns[2].mean, None);
+        assert_eq!(stats.columns[3].column_type, "boolean");
+        assert_eq!(stats.columns[3].nulls, 1);
+        Ok(())
+    }
+
+    // 测试超过上限时内存有界，整数最小、最大值不丢失精度
+    #[test]
+    fn test_collect_csv_stats_bounded() -> Result<()> {
+        let rows = MAX_NUMBER_SAMPLES + MAX_DISTINCT;
+        let mut input = String::from("n,big\n");
+        for i in 0..rows {
+            input.push_str(&format!(
+                "{},{}\n",
+                i,
+                9_007_199_254_740_993i64 + (i % 2) as i64
+            ));
+        }
+        let stats = collect_csv_stats(input.as_bytes(), &CsvStatsConfig::default())?;
+
+        let n = &stats.columns[0];
+        assert_eq!((n.distinct, n.distinct_truncated), (MAX_DISTINCT, true));
+        assert_eq!(
+            (n.min.clone(), n.max.clone()),
+            (Some(json!(0)), Some(json!(rows - 1)))
+        );
+        assert_eq!(n.mean, Some((rows - 1) as f64 / 2.0));
+        // 中位数根据抽样估算
+        let median = n.median.unwrap_or_default();
+        assert!((median - n.mean.unwrap_or_default()).abs() < rows as f64 * 0.01);
+        let total: usize = n.histogram.iter().map(|bin| bin.count).sum();
+        assert!(total.abs_diff(rows) <= n.histogram.len());
+
+        let big = &stats.columns[1];
+        assert_eq!(big.min, Some(json!(9_007_199_254_740_993i64)));
+        assert_eq!(big.max, Some(json!(9_007_199_254_740_994i64)));
+        assert!(!big.distinct_truncated);
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_stats_report() -> Result<()> {
+        let stats = collect_csv_stats(
+            fs::File::open("assets/juventus.csv")?,
+            &CsvStatsConfig::default(),
+        )?;
+        let kit_number = &stats.columns[4];
+        assert_eq!(kit_number.column_type, "integer");
+        assert_eq!(kit_number.distinct, stats.rows);
+        assert_eq!(
+            kit_number
+                .histogram
+                .iter()
+                .map(|bin| bin.count)
+                .sum::<usize>(),
+            stats.rows
+        );
+
+        let mut report = Vec::new();
+        stats.write_report(&mut report)?;
+        let report = String::from_utf8(report)?;
+        assert!(report.starts_with(&format!("行数: {}\n+", stats.rows)));
+        assert!(report.contains("\nNationality (string)\n  常见值:\n    Italy      8\n"));
+
+        let json = serde_json::to_value(&stats)?;
+        assert_eq!(
+            json["columns"][3]["top"][0],
+            json!({"value": "Italy", "count": 8})
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use std::io::Write;
+
+use anyhow::Result;
+use serde_json::Value;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// 表格的边框样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableStyle {
+    // +---+ 形式的边框
+    Ascii,
+    // ┌───┐ 形式的边框
+    Unicode,
+}
+
+impl TableStyle {
+    // 上边框、表头分隔线、下边框的左、中、右字符及横线字符，以及竖线字符
+    fn top(self) -> [char; 4] {
+        match self {
+            TableStyle::Ascii => ['+', '+', '+', '-'],
+            TableStyle::Unicode => ['┌', '┬', '┐', '─'],
+        }
+    }
+
+    fn separator(self) -> [char; 4] {
+        match self {
+            TableStyle::Ascii => ['+', '+', '+', '-'],
+            TableStyle::Unicode => ['├', '┼', '┤', '─'],
+        }
+    }
+
+    fn bottom(self) -> [char; 4] {
+        match self {
+            TableStyle::Ascii => ['+', '+', '+', '-'],
+            TableStyle::Unicode => ['└', '┴', '┘', '─'],
+        }
+    }
+
+    fn vertical(self) -> char {
+        match self {
+            TableStyle::Ascii => '|',
+            TableStyle::Unicode => '│',
+        }
+    }
+}
+
+// 输出为终端表格，数字右对齐，null显示为 NULL
+pub fn write_text_table(
+    writer: &mut impl Write,
+    columns: &[String],
+    rows: &[Vec<Value>],
+) -> Result<()> {
+    write_table(writer, columns, rows, TableStyle::Ascii, 0)
+}
+
+// 输出为终端表格，列宽按显示宽度计算（中日韩字符占两列），max_width大于0时截断超出宽度的单元格
+pub fn write_table(
+    writer: &mut impl Write,
+    columns: &[String],
+    rows: &[Vec<Value>],
+    style: TableStyle,
+    max_width: usize,
+) -> Result<()> {
+    let columns: Vec<String> = columns
+        .iter()
+        .map(|column| truncate(&single_line(column), max_width))
+        .collect();
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|value| truncate(&cell_text(value), max_width))
+                .collect()
+        })
+        .collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            cells
+                .iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| cell.width())
+                .chain([column.width()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let vertical = style.vertical();
+    writeln!(writer, "{}", border(&widths, style.top()))?;
+    write_table_row(
+        writer,
+        columns.iter().map(|c| (c.as_str(), false)),
+        &widths,
+        vertical,
+    )?;
+    if cells.is_empty() {
+        writeln!(writer, "{}", border(&widths, style.bottom()))?;
+        return Ok(());
+    }
+    writeln!(writer, "{}", border(&widths, style.separator()))?;
+    for (row, values) in cells.iter().zip(rows) {
+        let cells = row
+            .iter()
+            .zip(values)
+            .map(|(cell, value)| (cell.as_str(), value.is_number()));
+        write_table_row(writer, cells, &widths, vertical)?;
+    }
+    writeln!(writer, "{}", border(&widths, style.bottom()))?;
+    Ok(())
+}
+
+fn border(widths: &[usize], [left, middle, right, line]: [char; 4]) -> String {
+    let parts: Vec<String> = widths
+        .iter()
+        .map(|width| line.to_string().repeat(width + 2))
+        .collect();
+    format!("{}{}{}", left, parts.join(&middle.to_string()), right)
+}
+
+fn write_table_row<'a>(
+    writer: &mut impl Write,
+    cells: impl Iterator<Item = (&'a str, bool)>,
+    widths: &[usize],
+    vertical: char,
+) -> Result<()> {
+    let line: Vec<String> = cells
+        .zip(widths)
+        .map(|((cell, right), width)| {
+            // 格式化的宽度按字符数计算，需要按显示宽度手动补齐
+            let padding = " ".repeat(width.saturating_sub(cell.width()));
+            if right {
+                format!(" {}{} ", padding, cell)
+            } else {
+                format!(" {}{} ", cell, padding)
+            }
+        })
+        .collect();
+    writeln!(
+        writer,
+        "{}{}{}",
+        vertical,
+        line.join(&vertical.to_string()),
+        vertical
+    )?;
+    Ok(())
+}
+
+// 换行符及制表符会破坏表格，替换为空格
+fn single_line(s: &str) -> String {
+    s.replace(['\r', '\n', '\t'], " ")
+}
+
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::String(s) => single_line(s),
+        value => value.to_string(),
+    }
+}
+
+// 截断显示宽度超过max_width的内容，末尾使用 … 表示
+fn truncate(s: &str, max_width: usize) -> String {
+    if max_width == 0 || s.width() <= max_width {
+        return s.to_string();
+    }
+    let mut width = 0;
+    let mut result = String::new();
+    for c in s.chars() {
+        let char_width = c.width().unwrap_or_default();
+        if width + char_width > max_width - 1 {
+            break;
+        }
+        width += char_width;
+        result.push(c);
+    }
+    result.push('…');
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_write_table() -> Result<()> {
+        let columns = vec!["姓名".to_string(), "age".to_string()];
+        let rows = vec![
+            vec![json!("张三"), json!(30)],
+            vec![json!("Bob"), json!(5)],
+            vec![json!("欧阳娜娜娜娜"), Value::Null],
+        ];
+        let mut output = Vec::new();
+        write_table(&mut output, &columns, &rows, TableStyle::Unicode, 8)?;
+        assert_eq!(
+            String::from_utf8(output)?,
+            "┌─────────┬──────┐\n\
+             │ 姓名    │ age  │\n\
+             ├─────────┼──────┤\n\
+             │ 张三    │   30 │\n\
+             │ Bob     │    5 │\n\
+             │ 欧阳娜… │ NULL │\n\
+             └─────────┴──────┘\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use anyhow::{anyhow, Result};
+use csv::StringRecord;
+use serde_json::{Map, Value};
+
+use super::{
+    filter::{compare_values, parse_expr, Expr},
+    CsvConvertConfig,
+};
+
+// 排序字段，如 age:desc
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvSortKey {
+    pub column: String,
+    pub descending: bool,
+}
+
+// 排序字段的值以及处理后的记录
+pub type TransformedRecord = (Vec<Value>, Map<String, Value>);
+
+// 对每条记录依次执行过滤、列选择、重命名，过滤和排序均使用原始列名
+pub struct RecordTransform {
+    filter: Option<Expr>,
+    select: Vec<String>,
+    rename: HashMap<String, String>,
+    sort_by: Vec<CsvSortKey>,
+}
+
+// 在表头中查找列，优先精确匹配，其次忽略大小写匹配
+fn resolve_column(headers: &StringRecord, name: &str) -> Result<String> {
+    headers
+        .iter()
+        .find(|header| *header == name)
+        .or_else(|| {
+            headers
+                .iter()
+                .find(|header| header.eq_ignore_ascii_case(name))
+        })
+        .map(|header| header.to_string())
+        .ok_or_else(|| anyhow!("列不存在: {}", name))
+}
+
+impl RecordTransform {
+    pub fn new(headers: &StringRecord, config: &CsvConvertConfig) -> Result<Self> {
+        let filter = config.filter.as_deref().map(parse_expr).transpose()?;
+        if let Some(filter) = &filter {
+            for column in filter.columns() {
+                resolve_column(headers, column)?;
+            }
+        }
+
+        let select = config
+            .select
+            .iter()
+            .map(|column| resolve_column(headers, column))
+            .collect::<Result<_>>()?;
+        let rename = config
+            .rename
+            .iter()
+            .map(|(from, to)| Ok((resolve_column(headers, from)?, to.clone())))
+            .collect::<Result<_>>()?;
+        let sort_by = config
+            .sort_by
+            .iter()
+            .map(|key| {
+                Ok(CsvSortKey {
+                    column: resolve_column(headers, &key.column)?,
+                    descending: key.descending,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            filter,
+            select,
+            rename,
+            sort_by,
+        })
+    }
+
+    pub fn is_sorted(&self) -> bool {
+        !self.sort_by.is_empty()
+    }
+
+    // 返回排序字段的值以及处理后的记录，不满足过滤条件时返回None
+    pub fn apply(&self, map: Map<String, Value>) -> Result<Option<TransformedRecord>> {
+        if let Some(filter) = &self.filter {
+            if !filter.eval(&map)? {
+                return Ok(None);
+            }
+        }
+
+        let sort_values = self
+            .sort_by
+            .iter()
+            .map(|key| map.get(&key.column).cloned().unwrap_or_default())
+            .collect();
+
+        let map = if self.select.is_empty() {
+            map
+        } else {
+            let mut map = map;
+            self.select
+                .iter()
+                .filter_map(|column| map.remove_entry(column))
+                .collect()
+        };
+
+        let map = if self.rename.is_empty() {
+            map
+        } else {
+            map.into_iter()
+                .map(|(key, value)| match self.rename.get(&key) {
+                    Some(new_key) => (new_key.clone(), value),
+                    None => (key, value),
+                })
+                .collect()
+        };
+
+        Ok(Some((sort_values, map)))
+    }
+
+    // 按排序字段比较，null始终排在最后
+    pub fn compare(&self, left: &[Value], right: &[Value]) -> Ordering {
+        for (key, (l, r)) in self.sort_by.iter().zip(left.iter().zip(right)) {
+            let ordering = match (l.is_null(), r.is_null()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => return Ordering::Greater,
+                (false, true) => return Ordering::Less,
+                _ if key.descending => compare_values(r, l),
+                _ => compare_values(l, r),
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+}
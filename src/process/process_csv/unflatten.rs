@@ -0,0 +1,214 @@
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+
+// 数组下标的上限，避免 a[4000000000000] 这样的列名分配大量内存
+const MAX_INDEX: usize = 10_000;
+
+// 列名中的路径片段
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+// 解析 address.city、tags[0]、items[1].name 形式的列名
+fn parse_path<'a>(key: &'a str, separator: &str) -> Result<Vec<Segment<'a>>> {
+    if separator.is_empty() {
+        return Err(anyhow!("分隔符不能为空"));
+    }
+    let mut segments = Vec::new();
+    for part in key.split(separator) {
+        let (name, mut rest) = match part.find('[') {
+            Some(pos) => part.split_at(pos),
+            None => (part, ""),
+        };
+        if !name.is_empty() {
+            segments.push(Segment::Key(name));
+        }
+        while !rest.is_empty() {
+            let end = rest
+                .find(']')
+                .ok_or_else(|| anyhow!("列名中的 [ 没有闭合: {}", key))?;
+            let index: usize = rest[1..end]
+                .trim()
+                .parse()
+                .map_err(|_| anyhow!("列名中的数组下标无效: {}", key))?;
+            if index >= MAX_INDEX {
+                return Err(anyhow!(
+                    "列名中的数组下标不能大于{}: {}",
+                    MAX_INDEX - 1,
+                    key
+                ));
+            }
+            segments.push(Segment::Index(index));
+            rest = &rest[end + 1..];
+            if !rest.is_empty() && !rest.starts_with('[') {
+                return Err(anyhow!("列名格式错误: {}", key));
+            }
+        }
+    }
+    if segments.is_empty() {
+        return Err(anyhow!("列名为空"));
+    }
+    Ok(segments)
+}
+
+// 根据列名中的分隔符和下标还原嵌套的对象和数组
+pub fn unflatten(map: Map<String, Value>, separator: &str) -> Result<Value> {
+    let (keys, values): (Vec<String>, Vec<Value>) = map.into_iter().unzip();
+    let paths = keys
+        .iter()
+        .map(|key| parse_path(key, separator))
+        .collect::<Result<Vec<_>>>()?;
+    // 一列的路径是另一列的前缀时（如 a 与 a.b）结构冲突，与列的顺序及值是否为空无关
+    // 排序后以某路径为前缀的路径紧随其后，只需比较相邻的两项
+    let mut order: Vec<usize> = (0..paths.len()).collect();
+    order.sort_by(|&l, &r| paths[l].cmp(&paths[r]));
+    for pair in order.windows(2) {
+        if paths[pair[1]].starts_with(&paths[pair[0]]) {
+            return Err(anyhow!(
+                "列 \"{}\" 与列 \"{}\" 的结构冲突",
+                keys[pair[0]],
+                keys[pair[1]]
+            ));
+        }
+    }
+
+    let mut root = Value::Object(Map::new());
+    for ((key, segments), value) in keys.iter().zip(&paths).zip(values) {
+        insert(&mut root, segments, value).map_err(|e| anyhow!("列 \"{}\": {}", key, e))?;
+    }
+    Ok(root)
+}
+
+fn insert(node: &mut Value, segments: &[Segment], value: Value) -> Result<()> {
+    let Some((segment, rest)) = segments.split_first() else {
+        return Ok(());
+    };
+
+    let child = match segment {
+        Segment::Key(key) => {
+            if node.is_null() {
+                *node = Value::Object(Map::new());
+            }
+            let Value::Object(map) = node else {
+                return Err(anyhow!("\"{}\" 与其他列的结构冲突", key));
+            };
+            map.entry(key.to_string()).or_insert(Value::Null)
+        }
+        Segment::Index(index) => {
+            if node.is_null() {
+                *node = Value::Array(Vec::new());
+            }
+            let Value::Array(arr) = node else {
+                return Err(anyhow!("下标 [{}] 与其他列的结构冲突", index));
+            };
+            if arr.len() <= *index {
+                arr.resize(*index + 1, Value::Null);
+            }
+            &mut arr[*index]
+        }
+    };
+
+    if rest.is_empty() {
+        if !child.is_null() {
+            return Err(anyhow!("与其他列的结构冲突"));
+        }
+        *child = value;
+        Ok(())
+    } else {
+        insert(child, rest, value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_path() -> Result<()> {
+        assert_eq!(
+            parse_path("items[1].name", ".")?,
+            vec![
+                Segment::Key("items"),
+                Segment::Index(1),
+                Segment::Key("name")
+            ]
+        );
+        assert_eq!(
+            parse_path("matrix[0][2]", ".")?,
+            vec![Segment::Key("matrix"), Segment::Index(0), Segment::Index(2)]
+        );
+        assert_eq!(
+            parse_path("address__city", "__")?,
+            vec![Segment::Key("address"), Segment::Key("city")]
+        );
+        assert!(parse_path("tags[x]", ".").is_err());
+        assert!(parse_path("tags[0", ".").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_path_index_too_large() {
+        for key in ["a[18446744073709551615]", "a[4000000000000]"] {
+            let err = parse_path(key, ".").unwrap_err();
+            assert!(err.to_string().contains("不能大于9999"), "{}", err);
+        }
+        assert!(parse_path("a[10000]", ".").is_err());
+        assert!(parse_path("a[9999]", ".").is_ok());
+    }
+
+    #[test]
+    fn test_unflatten() -> Result<()> {
+        let map = json!({
+            "name": "Buffon",
+            "address.city": "Turin",
+            "address.zip": "10100",
+            "tags[1]": "captain",
+            "tags[0]": "goalkeeper",
+            "clubs[0].name": "Parma",
+            "clubs[1].name": "Juventus"
+        });
+        let Value::Object(map) = map else {
+            unreachable!()
+        };
+
+        assert_eq!(
+            unflatten(map, ".")?,
+            json!({
+                "name": "Buffon",
+                "address": {"city": "Turin", "zip": "10100"},
+                "tags": ["goalkeeper", "captain"],
+                "clubs": [{"name": "Parma"}, {"name": "Juventus"}]
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unflatten_conflict() {
+        let map = json!({"address": "Turin", "address.city": "Turin"});
+        let Value::Object(map) = map else {
+            unreachable!()
+        };
+        assert!(unflatten(map, ".").is_err());
+    }
+
+    // 测试空值占据的路径同样会与子路径冲突，且与列的顺序无关
+    #[test]
+    fn test_unflatten_conflict_null() {
+        for map in [
+            json!({"a": null, "a.b": "x"}),
+            json!({"a.b": "x", "a": null}),
+            json!({"a[0]": null, "a[0].b": "x"}),
+            json!({"a[0].b": "x", "a[0]": null}),
+        ] {
+            let Value::Object(map) = map else {
+                unreachable!()
+            };
+            let err = unflatten(map, ".").unwrap_err();
+            assert!(err.to_string().contains("结构冲突"), "{}", err);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::utils::{get_reader_from_path, get_writer_from_path};
+
+use super::{
+    dialect::{build_reader, read_headers},
+    infer::infer_value,
+    table::{write_table, TableStyle},
+    CsvDialect,
+};
+
+// 终端表格查看的配置
+#[derive(Debug, Clone)]
+pub struct CsvViewConfig {
+    pub dialect: CsvDialect,
+    // 推断字段类型，数字右对齐
+    pub infer_types: bool,
+    // 只显示指定的列
+    pub columns: Vec<String>,
+    // 最多显示的列数，0为不限制
+    pub max_columns: usize,
+    // 单元格的最大显示宽度，0为不截断
+    pub max_width: usize,
+    // 显示的页码，从1开始
+    pub page: usize,
+    // 每页的行数，0为显示全部
+    pub page_size: usize,
+    pub style: TableStyle,
+}
+
+impl Default for CsvViewConfig {
+    fn default() -> Self {
+        Self {
+            dialect: CsvDialect::default(),
+            infer_types: true,
+            columns: Vec::new(),
+            max_columns: 0,
+            max_width: 40,
+            page: 1,
+            page_size: 50,
+            style: TableStyle::Unicode,
+        }
+    }
+}
+
+// 当前页的数据
+struct ViewPage {
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    total: usize,
+}
+
+impl CsvViewConfig {
+    // 当前页在全部行中的范围，页码过大时为空范围
+    fn range(&self) -> (usize, usize) {
+        if self.page_size == 0 {
+            return (0, usize::MAX);
+        }
+        let start = self.page.saturating_sub(1).saturating_mul(self.page_size);
+        (start, start.saturating_add(self.page_size))
+    }
+
+    // 需要显示的列在全部列中的位置
+    fn column_indexes(&self, columns: &[String]) -> Result<Vec<usize>> {
+        let mut indexes = if self.columns.is_empty() {
+            (0..columns.len()).collect()
+        } else {
+            self.columns
+                .iter()
+                .map(|name| {
+                    columns
+                        .iter()
+                        .position(|column| column == name)
+                        .ok_or_else(|| anyhow!("列不存在: {}", name))
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
+        if self.max_columns > 0 {
+            indexes.truncate(self.max_columns);
+        }
+        Ok(indexes)
+    }
+}
+
+// 逐行读取csv，只保留当前页的行
+fn read_csv_page<R: Read>(input: R, config: &CsvViewConfig) -> Result<(Vec<String>, ViewPage)> {
+    let mut reader = build_reader(input, &config.dialect)?;
+    let headers: Vec<String> = read_headers(&mut reader, &config.dialect)?
+        .iter()
+        .map(String::from)
+        .collect();
+    let indexes = config.column_indexes(&headers)?;
+    let (start, end) = config.range();
+
+    let mut rows = Vec::new();
+    let mut total = 0;
+    for record in reader.records() {
+        let record = record?;
+        if (start..end).contains(&total) {
+            let row = indexes
+                .iter()
+                .map(|&i| match record.get(i) {
+                    Some(field) if config.infer_types => infer_value(field),
+                    Some(field) => Value::String(field.to_string()),
+                    None => Value::Null,
+                })
+                .collect();
+            rows.push(row);
+        }
+        total += 1;
+    }
+    let columns = indexes.iter().map(|&i| headers[i].clone()).collect();
+    Ok((
+        headers,
+        ViewPage {
+            columns,
+            rows,
+            total,
+        },
+    ))
+}
+
+// 读取json对象数组，列为所有对象字段的并集，嵌套的值显示为json
+fn read_json_page<R: Read>(input: R, config: &CsvViewConfig) -> Result<(Vec<String>, ViewPage)> {
+    let values: Vec<Value> =
+        serde_json::from_reader(input).map_err(|e| anyhow!("json内容应为对象数组: {}", e))?;
+    let mut headers: Vec<String> = Vec::new();
+    for value in &values {
+        let object = value
+            .as_object()
+            .ok_or_else(|| anyhow!("json内容应为对象数组"))?;
+        for key in object.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+    let indexes = config.column_indexes(&headers)?;
+    let (start, end) = config.range();
+
+    let rows = values
+        .iter()
+        .skip(start)
+        .take(end - start)
+        .map(|value| {
+            indexes
+                .iter()
+                .map(|&i| match value.get(&headers[i]) {
+                    Some(value @ (Value::Array(_) | Value::Object(_))) => {
+                        Value::String(value.to_string())
+                    }
+                    Some(value) => value.clone(),
+                    None => Value::Null,
+                })
+                .collect()
+        })
+        .collect();
+    let columns = indexes.iter().map(|&i| headers[i].clone()).collect();
+    Ok((
+        headers,
+        ViewPage {
+            columns,
+            rows,
+            total: values.len(),
+        },
+    ))
+}
+
+// 以表格形式显示csv或json对象数组（以 [ 开头的内容）中的一页，表格下方显示页码及隐藏的列数
+pub fn view_csv<R: Read, W: Write>(input: R, output: &mut W, config: &CsvViewConfig) -> Result<()> {
+    let mut input = BufReader::new(input);
+    let is_json = input
+        .fill_buf()?
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|&b| b == b'[');
+    let (headers, page) = if is_json {
+        read_json_page(input, config)?
+    } else {
+        read_csv_page(input, config)?
+    };
+
+    write_table(
+        output,
+        &page.columns,
+        &page.rows,
+        config.style,
+        config.max_width,
+    )?;
+    let mut footer = if config.page_size > 0 && page.total > config.page_size {
+        format!(
+            "第{}/{}页，共{}行",
+            config.page,
+            page.total.div_ceil(config.page_size),
+            page.total
+        )
+    } else {
+        format!("共{}行", page.total)
+    };
+    if page.columns.len() < headers.len() {
+        footer.push_str(&format!(
+            "，{}列中显示{}列",
+            headers.len(),
+            page.columns.len()
+        ));
+    }
+    writeln!(output, "{}", footer)?;
+    Ok(())
+}
+
+// 以表格形式显示csv文件
+pub fn view_csv_in_file(input_path: &str, save_path: &str, config: &CsvViewConfig) -> Result<()> {
+    let mut output = get_writer_from_path(save_path)?;
+    view_csv(get_reader_from_path(input_path)?, &mut output, config)?;
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn view(input: &str, config: &CsvViewConfig) -> Result<String> {
+        let mut output = Vec::new();
+        view_csv(input.as_bytes(), &mut output, config)?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    #[test]
+    fn test_view_csv_page() -> Result<()> {
+        let config = CsvViewConfig {
+            columns: vec!["Name".to_string(), "Kit Number".to_string()],
+            page: 2,
+            page_size: 2,
+            ..Default::default()
+        };
+        let input = std::fs::read_to_string("assets/juventus.csv")?;
+        let output = view(&input, &config)?;
+        assert_eq!(
+            output,
+            "┌──────────────────┬────────────┐\n\
+             │ Name             │ Kit Number │\n\
+             ├──────────────────┼────────────┤\n\
+             │ Gianluigi Buffon │         77 │\n\
+             │ Carlo Pinsoglio  │         31 │\n\
+             └──────────────────┴────────────┘\n\
+             第2/14页，共27行，5列中显示2列\n"
+        );
+        Ok(())
+    }
+
+    // 测试页码或每页行数过大时不会溢出
+    #[test]
+    fn test_view_csv_page_overflow() -> Result<()> {
+        let config = CsvViewConfig {
+            page: usize::MAX,
+            page_size: 2,
+            ..Default::default()
+        };
+        assert_eq!(config.range(), (usize::MAX, usize::MAX));
+        let config = CsvViewConfig {
+            page_size: usize::MAX,
+            ..Default::default()
+        };
+        assert_eq!(config.range(), (0, usize::MAX));
+
+        let config = CsvViewConfig {
+            page: usize::MAX,
+            page_size: 1,
+            ..Default::default()
+        };
+        let output = view("id\n1\n2\n", &config)?;
+        assert!(output.ends_with(&format!("第{}/2页，共2行\n", usize::MAX)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_view_json() -> Result<()> {
+        let input = r#"[{"name": "张三", "tags": ["a"]}, {"name": "Bob", "age": 5}]"#;
+        let output = view(input, &CsvViewConfig::default())?;
+        assert_eq!(
+            output,
+            "┌──────┬───────┬──────┐\n\
+             │ name │ tags  │ age  │\n\
+             ├──────┼───────┼──────┤\n\
+             │ 张三 │ [\"a\"] │ NULL │\n\
+             │ Bob  │ NULL  │    5 │\n\
+             └──────┴───────┴──────┘\n\
+             共2行\n"
+        );
+        Ok(())
+    }
+}
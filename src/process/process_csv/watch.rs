@@ -0,0 +1,89 @@
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+
+use super::batch::glob_files;
+
+// 检查文件变化的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+// 文件在该时间内没有再变化后才重新转换，避免编辑器保存时的多次写入触发多次转换
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+// 文件的修改时间及大小
+type Snapshot = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+fn snapshot(pattern: &str) -> Result<Snapshot> {
+    Ok(glob_files(pattern)?
+        .into_iter()
+        .filter_map(|path| {
+            let meta = path.metadata().ok()?;
+            Some((path, (meta.modified().ok(), meta.len())))
+        })
+        .collect())
+}
+
+// 新增或修改的文件，删除的文件不需要重新转换
+fn changed_files(old: &Snapshot, new: &Snapshot) -> Vec<PathBuf> {
+    new.iter()
+        .filter(|(path, state)| old.get(*path) != Some(state))
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+// 监听文件或glob模式匹配的文件，有文件新增或修改时以变化的文件调用 on_change，不会返回
+pub fn watch_csv_inputs<F: FnMut(&[PathBuf])>(pattern: &str, mut on_change: F) -> Result<()> {
+    let mut known = snapshot(pattern)?;
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let mut current = snapshot(pattern)?;
+        if current == known {
+            continue;
+        }
+        loop {
+            thread::sleep(DEBOUNCE);
+            let next = snapshot(pattern)?;
+            if next == current {
+                break;
+            }
+            current = next;
+        }
+        let changed = changed_files(&known, &current);
+        known = current;
+        if !changed.is_empty() {
+            on_change(&changed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_changed_files() {
+        let time = SystemTime::UNIX_EPOCH;
+        let later = time + Duration::from_secs(1);
+        let old: Snapshot = [
+            (PathBuf::from("a.csv"), (Some(time), 10)),
+            (PathBuf::from("b.csv"), (Some(time), 10)),
+            (PathBuf::from("c.csv"), (Some(time), 10)),
+        ]
+        .into();
+        let new: Snapshot = [
+            (PathBuf::from("a.csv"), (Some(time), 10)),
+            (PathBuf::from("b.csv"), (Some(later), 10)),
+            (PathBuf::from("d.csv"), (Some(time), 5)),
+        ]
+        .into();
+        assert_eq!(
+            changed_files(&old, &new),
+            vec![PathBuf::from("b.csv"), PathBuf::from("d.csv")]
+        );
+        assert!(changed_files(&new, &new).is_empty());
+    }
+}
@@ -0,0 +1,419 @@
+use std::{
+    collections::HashSet,
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+
+use crate::CsvFormatType;
+
+// 逐条写出转换后的记录，只保留当前记录在内存中
+// MessagePack的数组需要预先写出长度，因此已编码的记录先写入临时文件
+pub struct RecordWriter<W: Write> {
+    writer: W,
+    format: CsvFormatType,
+    toml_key: String,
+    toml_key_column: Option<String>,
+    // keyed模式下已出现的表名，用于检查重复
+    toml_keys: HashSet<String>,
+    // markdown、html表格的列名，取自第一条记录，之后的记录不能包含新的列
+    columns: Vec<String>,
+    // 已编码的MessagePack记录
+    msgpack: Option<Spool>,
+    count: usize,
+}
+
+impl<W: Write> RecordWriter<W> {
+    pub fn new(
+        writer: W,
+        format: CsvFormatType,
+        toml_key: String,
+        toml_key_column: Option<String>,
+    ) -> Self {
+        Self {
+            writer,
+            format,
+            toml_key,
+            toml_key_column,
+            toml_keys: HashSet::new(),
+            columns: Vec::new(),
+            msgpack: None,
+            count: 0,
+        }
+    }
+
+    // 指定markdown、html表格的列顺序，未指定时取第一条记录的字段
+    pub fn with_columns(mut self, columns: Vec<String>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn write(&mut self, record: Value) -> Result<()> {
+        match self.format {
+            CsvFormatType::Json => {
+                // 与 serde_json::to_string_pretty 输出整个数组的格式保持一致
+                let sep = if self.count == 0 { "[\n" } else { ",\n" };
+                self.writer.write_all(sep.as_bytes())?;
+                let pretty = serde_json::to_string_pretty(&record)?;
+                for (i, line) in pretty.lines().enumerate() {
+                    if i > 0 {
+                        self.writer.write_all(b"\n")?;
+                    }
+                    write!(self.writer, "  {}", line)?;
+                }
+            }
+            CsvFormatType::Ndjson => {
+                serde_json::to_writer(&mut self.writer, &record)?;
+                self.writer.write_all(b"\n")?;
+            }
+            CsvFormatType::Yaml => {
+                serde_yaml::to_writer(&mut self.writer, &[record])?;
+            }
+            CsvFormatType::Toml => self.write_toml(record)?,
+            CsvFormatType::Xml => {
+                if self.count == 0 {
+                    self.writer
+                        .write_all(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<records>\n")?;
+                }
+                write_xml_element(&mut self.writer, "record", &record, 1)?;
+            }
+            CsvFormatType::Markdown => self.write_markdown(record)?,
+            CsvFormatType::Html => self.write_html(record)?,
+            CsvFormatType::MessagePack => {
+                let spool = match &mut self.msgpack {
+                    Some(spool) => spool,
+                    None => self.msgpack.insert(Spool::new()?),
+                };
+                rmp_serde::encode::write(&mut spool.writer, &record)?;
+            }
+            CsvFormatType::Cbor => {
+                // 使用不定长数组，无需预先知道记录数
+                if self.count == 0 {
+                    self.writer.write_all(&[0x9f])?;
+                }
+                ciborium::into_writer(&record, &mut self.writer)?;
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    // 写出结尾并返回写出的记录数
+    pub fn finish(mut self) -> Result<usize> {
+        match self.format {
+            CsvFormatType::Json if self.count == 0 => self.writer.write_all(b"[]")?,
+            CsvFormatType::Json => self.writer.write_all(b"\n]")?,
+            CsvFormatType::Yaml if self.count == 0 => self.writer.write_all(b"[]\n")?,
+            CsvFormatType::Xml if self.count == 0 => self
+                .writer
+                .write_all(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<records/>\n")?,
+            CsvFormatType::Xml => self.writer.write_all(b"</records>\n")?,
+            CsvFormatType::Html if self.count == 0 => {
+                self.writer.write_all(b"<table>\n</table>\n")?
+            }
+            CsvFormatType::Html => self.writer.write_all(b"  </tbody>\n</table>\n")?,
+            CsvFormatType::MessagePack => {
+                write_msgpack_array_len(&mut self.writer, self.count)?;
+                if let Some(spool) = &mut self.msgpack {
+                    spool.copy_to(&mut self.writer)?;
+                }
+            }
+            CsvFormatType::Cbor if self.count == 0 => self.writer.write_all(&[0x80])?,
+            CsvFormatType::Cbor => self.writer.write_all(&[0xff])?,
+            _ => {}
+        }
+        self.writer.flush()?;
+        Ok(self.count)
+    }
+
+    fn write_toml(&mut self, record: Value) -> Result<()> {
+        let record = strip_toml_nulls(record, &format!("第{}条记录", self.count + 1))?;
+        let table = match self.toml_key_column.clone() {
+            Some(column) => {
+                let (key, record) = self.split_toml_key(record, &column)?;
+                Value::Object(Map::from_iter([(key, record)]))
+            }
+            None => Value::Array(vec![record]),
+        };
+        let toml_str = toml::to_string_pretty(&Map::from_iter([(self.toml_key.clone(), table)]))?;
+        if self.count > 0 {
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.write_all(toml_str.as_bytes())?;
+        Ok(())
+    }
+
+    // 以指定列的值作为表名，如 [players.Buffon]，该列不再出现在表内
+    fn split_toml_key(&mut self, record: Value, column: &str) -> Result<(String, Value)> {
+        let line = self.count + 1;
+        let Value::Object(mut record) = record else {
+            return Err(anyhow!("第{}条记录不是对象", line));
+        };
+        let key = match record.remove(column) {
+            Some(Value::String(s)) if !s.is_empty() => s,
+            Some(Value::Null) | Some(Value::String(_)) => {
+                return Err(anyhow!(
+                    "第{}条记录的 \"{}\" 列为空，无法作为表名",
+                    line,
+                    column
+                ))
+            }
+            Some(value) => value.to_string(),
+            None => return Err(anyhow!("作为表名的列不存在: {}", column)),
+        };
+        if !self.toml_keys.insert(key.clone()) {
+            return Err(anyhow!("\"{}\" 列存在重复的值: {}", column, key));
+        }
+        Ok((key, Value::Object(record)))
+    }
+
+    // 表格的表头在第一条记录时就已输出，之后出现的新列无法再加入，直接报错而不是静默丢弃
+    fn check_columns(&self, record: &Value) -> Result<()> {
+        if let Some(map) = record.as_object() {
+            if let Some(key) = map.keys().find(|key| !self.columns.contains(key)) {
+                return Err(anyhow!(
+                    "第{}条记录包含表头中不存在的列: {}",
+                    self.count + 1,
+                    key
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn write_markdown(&mut self, record: Value) -> Result<()> {
+        if self.count == 0 {
+            if self.columns.is_empty() {
+                self.columns = record_columns(&record);
+            }
+            let header: Vec<String> = self.columns.iter().map(|c| escape_markdown(c)).collect();
+            writeln!(self.writer, "| {} |", header.join(" | "))?;
+            writeln!(self.writer, "|{}", " --- |".repeat(self.columns.len()))?;
+        }
+        self.check_columns(&record)?;
+        let cells: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| escape_markdown(&cell_text(record.get(column))))
+            .collect();
+        writeln!(self.writer, "| {} |", cells.join(" | "))?;
+        Ok(())
+    }
+
+    fn write_html(&mut self, record: Value) -> Result<()> {
+        if self.count == 0 {
+            if self.columns.is_empty() {
+                self.columns = record_columns(&record);
+            }
+            self.writer.write_all(b"<table>\n  <thead>\n    <tr>\n")?;
+            for column in &self.columns {
+                writeln!(self.writer, "      <th>{}</th>", escape_xml(column))?;
+            }
+            self.writer
+                .write_all(b"    </tr>\n  </thead>\n  <tbody>\n")?;
+        }
+        self.check_columns(&record)?;
+        self.writer.write_all(b"    <tr>\n")?;
+        for column in &self.columns {
+            let text = cell_text(record.get(column));
+            writeln!(self.writer, "      <td>{}</td>", escape_xml(&text))?;
+        }
+        self.writer.write_all(b"    </tr>\n")?;
+        Ok(())
+    }
+}
+
+// toml没有null，对象中的null字段（如开启类型推断后的空单元格）直接省略，数组中的null无法省略则报错
+fn strip_toml_nulls(value: Value, path: &str) -> Result<Value> {
+    match value {
+        Value::Object(map) => {
+            let mut result = Map::with_capacity(map.len());
+            for (key, value) in map {
+                if !value.is_null() {
+                    let value = strip_toml_nulls(value, &format!("{}.{}", path, key))?;
+                    result.insert(key, value);
+                }
+            }
+            Ok(Value::Object(result))
+        }
+        Value::Array(arr) => arr
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| match value {
+                Value::Null => Err(anyhow!("toml数组不支持null值，位置: {}[{}]", path, i)),
+                value => strip_toml_nulls(value, &format!("{}[{}]", path, i)),
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Value::Array),
+        value => Ok(value),
+    }
+}
+
+// 临时文件，drop时删除
+struct Spool {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl Spool {
+    fn new() -> Result<Self> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let path = env::temp_dir().join(format!(
+            "rrcli-{}-{}.msgpack",
+            process::id(),
+            NEXT_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    // 将写入的内容复制到输出
+    fn copy_to(&mut self, output: &mut impl Write) -> Result<()> {
+        self.writer.flush()?;
+        let file = self.writer.get_mut();
+        file.seek(SeekFrom::Start(0))?;
+        io::copy(file, output)?;
+        Ok(())
+    }
+}
+
+impl Drop for Spool {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn record_columns(record: &Value) -> Vec<String> {
+    record
+        .as_object()
+        .map(|map| map.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+// 表格单元格的文本，嵌套结构输出为json
+fn cell_text(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+fn escape_markdown(s: &str) -> String {
+    s.replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 将列名转换为合法的xml元素名，非法字符替换为 _
+fn xml_name(name: &str) -> String {
+    let mut result: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !result.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        result.insert(0, '_');
+    }
+    result
+}
+
+// 对象输出为子元素，数组输出为多个同名元素，null输出为空元素
+fn write_xml_element(
+    writer: &mut impl Write,
+    name: &str,
+    value: &Value,
+    depth: usize,
+) -> Result<()> {
+    let indent = "  ".repeat(depth);
+    let name = xml_name(name);
+    match value {
+        Value::Null => writeln!(writer, "{}<{}/>", indent, name)?,
+        Value::Object(map) => {
+            writeln!(writer, "{}<{}>", indent, name)?;
+            for (key, value) in map {
+                write_xml_element(writer, key, value, depth + 1)?;
+            }
+            writeln!(writer, "{}</{}>", indent, name)?;
+        }
+        Value::Array(arr) => {
+            for value in arr {
+                write_xml_element(writer, &name, value, depth)?;
+            }
+        }
+        Value::String(s) => writeln!(writer, "{}<{}>{}</{}>", indent, name, escape_xml(s), name)?,
+        value => writeln!(writer, "{}<{}>{}</{}>", indent, name, value, name)?,
+    }
+    Ok(())
+}
+
+fn write_msgpack_array_len(writer: &mut impl Write, len: usize) -> Result<()> {
+    match len {
+        0..=15 => writer.write_all(&[0x90 | len as u8])?,
+        16..=0xffff => {
+            writer.write_all(&[0xdc])?;
+            writer.write_all(&(len as u16).to_be_bytes())?;
+        }
+        _ => {
+            writer.write_all(&[0xdd])?;
+            writer.write_all(&u32::try_from(len)?.to_be_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    // 测试markdown、html表格遇到表头中不存在的列时报错而不是丢弃
+    #[test]
+    fn test_write_table_unknown_column() -> Result<()> {
+        for format in [CsvFormatType::Markdown, CsvFormatType::Html] {
+            let mut writer = RecordWriter::new(Vec::new(), format, String::new(), None);
+            writer.write(json!({"name": "a"}))?;
+            writer.write(json!({"name": "b"}))?;
+            let err = writer
+                .write(json!({"name": "c", "age": 1}))
+                .unwrap_err()
+                .to_string();
+            assert_eq!(err, "第3条记录包含表头中不存在的列: age");
+        }
+        Ok(())
+    }
+}
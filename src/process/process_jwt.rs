@@ -0,0 +1,300 @@
+use super::process_text::{
+    Ed25519Signer, Ed25519Verifier, HmacSha256, KeyLoader, TextSign, TextVerify,
+};
+use crate::{
+    process::{decode_base64, encode_base64},
+    Base64FormatType, JwtAlgorithm,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use serde_json::{json, Value};
+use std::fmt::Write;
+
+// 需要显示为可读时间的声明
+const TIME_CLAIMS: [&str; 3] = ["exp", "iat", "nbf"];
+
+// JWT的各段均使用不带填充的URL安全base64编码
+const JWT_BASE64: Base64FormatType = Base64FormatType::UrlSafe;
+
+// 解码后的JWT，signing_input为签名所覆盖的 header.claims 部分
+#[derive(Debug)]
+pub struct Jwt {
+    pub header: Value,
+    pub claims: Value,
+    signing_input: String,
+    signature: Vec<u8>,
+}
+
+// 验证JWT时需要检查的声明，now为当前的unix时间戳
+#[derive(Debug, Default)]
+pub struct JwtValidation {
+    pub audience: Option<String>,
+    pub issuer: Option<String>,
+    pub leeway: i64,
+    pub now: i64,
+}
+
+// 仅解码，不验证签名
+pub fn decode_jwt(token: &str) -> Result<Jwt> {
+    let parts: Vec<&str> = token.trim().split('.').collect();
+    let [header, claims, signature] = parts[..] else {
+        return Err(anyhow!("token格式错误: 应为以 . 分隔的三段内容"));
+    };
+    let header = decode_part(header, "header")?;
+    let claims = decode_part(claims, "claims")?;
+    if !claims.is_object() {
+        return Err(anyhow!("claims应为json对象"));
+    }
+    let signing_input = token.trim().rsplit_once('.').unwrap_or_default().0;
+    let signature = decode_base64(signature.as_bytes(), JWT_BASE64)
+        .map_err(|e| anyhow!("签名不是有效的base64url内容: {}", e))?;
+    Ok(Jwt {
+        header,
+        claims,
+        signing_input: signing_input.to_string(),
+        signature,
+    })
+}
+
+fn decode_part(part: &str, name: &str) -> Result<Value> {
+    let bytes = decode_base64(part.as_bytes(), JWT_BASE64)
+        .map_err(|e| anyhow!("{}不是有效的base64url内容: {}", name, e))?;
+    serde_json::from_slice(&bytes).map_err(|e| anyhow!("{}不是有效的json: {}", name, e))
+}
+
+// 使用密钥文件对claims签名，HS256使用任意长度的密钥文件，EdDSA使用ed25519私钥文件
+pub fn sign_jwt(claims: &Value, key: &str, algorithm: JwtAlgorithm) -> Result<String> {
+    if !claims.is_object() {
+        return Err(anyhow!("claims应为json对象"));
+    }
+    let header = json!({ "alg": algorithm.to_string(), "typ": "JWT" });
+    let signing_input = format!(
+        "{}.{}",
+        encode_base64(&serde_json::to_vec(&header)?, JWT_BASE64)?,
+        encode_base64(&serde_json::to_vec(claims)?, JWT_BASE64)?
+    );
+    let mut reader = signing_input.as_bytes();
+    let signature = match algorithm {
+        JwtAlgorithm::Hs256 => HmacSha256::load(key)?.sign(&mut reader)?,
+        JwtAlgorithm::EdDsa => Ed25519Signer::load(key)?.sign(&mut reader)?,
+    };
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        encode_base64(&signature, JWT_BASE64)?
+    ))
+}
+
+// 验证签名及exp、nbf、aud、iss声明，失败时返回说明原因的错误
+pub fn verify_jwt(
+    token: &str,
+    key: &str,
+    algorithm: JwtAlgorithm,
+    validation: &JwtValidation,
+) -> Result<Jwt> {
+    let jwt = decode_jwt(token)?;
+    // 只接受指定的算法，避免把公钥当作HMAC密钥使用的算法混淆攻击
+    let alg = jwt.header["alg"].as_str().unwrap_or_default();
+    if alg != algorithm.to_string() {
+        return Err(anyhow!(
+            "header中的算法 \"{}\" 与指定的算法 {} 不一致",
+            alg,
+            algorithm
+        ));
+    }
+    let mut reader = jwt.signing_input.as_bytes();
+    let valid = match algorithm {
+        JwtAlgorithm::Hs256 => HmacSha256::load(key)?.verify(&mut reader, &jwt.signature)?,
+        JwtAlgorithm::EdDsa => {
+            jwt.signature.len() == 64
+                && Ed25519Verifier::load(key)?.verify(&mut reader, &jwt.signature)?
+        }
+    };
+    if !valid {
+        return Err(anyhow!("签名验证失败: token已被篡改或密钥不匹配"));
+    }
+
+    if let Some(exp) = time_claim(&jwt.claims, "exp")? {
+        if validation.now >= exp.saturating_add(validation.leeway) {
+            return Err(anyhow!("token已于 {} 过期", format_timestamp(exp)));
+        }
+    }
+    if let Some(nbf) = time_claim(&jwt.claims, "nbf")? {
+        if validation.now.saturating_add(validation.leeway) < nbf {
+            return Err(anyhow!("token在 {} 之前不可用", format_timestamp(nbf)));
+        }
+    }
+    if let Some(audience) = &validation.audience {
+        // aud可以是字符串或字符串数组
+        let matched = match &jwt.claims["aud"] {
+            Value::String(aud) => aud == audience,
+            Value::Array(auds) => auds.iter().any(|aud| aud.as_str() == Some(audience)),
+            Value::Null => return Err(anyhow!("token中缺少aud声明，期望为 \"{}\"", audience)),
+            _ => false,
+        };
+        if !matched {
+            return Err(anyhow!(
+                "aud不匹配: 期望 \"{}\"，实际为 {}",
+                audience,
+                jwt.claims["aud"]
+            ));
+        }
+    }
+    if let Some(issuer) = &validation.issuer {
+        match &jwt.claims["iss"] {
+            Value::String(iss) if iss == issuer => {}
+            Value::Null => return Err(anyhow!("token中缺少iss声明，期望为 \"{}\"", issuer)),
+            iss => return Err(anyhow!("iss不匹配: 期望 \"{}\"，实际为 {}", issuer, iss)),
+        }
+    }
+    Ok(jwt)
+}
+
+fn time_claim(claims: &Value, name: &str) -> Result<Option<i64>> {
+    match &claims[name] {
+        Value::Null => Ok(None),
+        value => value
+            .as_f64()
+            .map(|v| Some(v as i64))
+            .ok_or_else(|| anyhow!("{}声明应为unix时间戳: {}", name, value)),
+    }
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    match DateTime::from_timestamp(timestamp, 0) {
+        Some(time) => time
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S %:z")
+            .to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+// 格式化输出header及claims，exp、iat、nbf附加可读时间及相对当前时间的状态
+pub fn format_jwt(jwt: &Jwt, now: i64) -> Result<String> {
+    let mut output = String::new();
+    writeln!(output, "Header:")?;
+    writeln!(output, "{}", serde_json::to_string_pretty(&jwt.header)?)?;
+    writeln!(output, "Claims:")?;
+    writeln!(output, "{}", serde_json::to_string_pretty(&jwt.claims)?)?;
+    let times: Vec<(&str, i64)> = TIME_CLAIMS
+        .iter()
+        .filter_map(|name| Some((*name, time_claim(&jwt.claims, name).ok()??)))
+        .collect();
+    if !times.is_empty() {
+        writeln!(output, "Times:")?;
+    }
+    for (name, timestamp) in times {
+        let status = match name {
+            "exp" if now >= timestamp => "已过期",
+            "exp" => "未过期",
+            "nbf" if now < timestamp => "尚未生效",
+            "nbf" => "已生效",
+            _ => "签发时间",
+        };
+        writeln!(
+            output,
+            "  {}: {} ({}，{})",
+            name,
+            timestamp,
+            format_timestamp(timestamp),
+            status
+        )?;
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn validation() -> JwtValidation {
+        JwtValidation {
+            now: NOW,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify_jwt() -> Result<()> {
+        let claims =
+            json!({ "sub": "1234", "iss": "rrcli", "aud": ["api", "web"], "exp": NOW + 60 });
+        let key = "fixtures/process_text/key.txt";
+        let token = sign_jwt(&claims, key, JwtAlgorithm::Hs256)?;
+        assert!(token.starts_with("eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9."));
+        let jwt = verify_jwt(&token, key, JwtAlgorithm::Hs256, &validation())?;
+        assert_eq!(jwt.claims, claims);
+
+        let token = sign_jwt(
+            &claims,
+            "fixtures/process_text/ed25519.sk",
+            JwtAlgorithm::EdDsa,
+        )?;
+        let mut check = JwtValidation {
+            audience: Some("web".to_string()),
+            issuer: Some("rrcli".to_string()),
+            ..validation()
+        };
+        let pk = "fixtures/process_text/ed25519.pk";
+        verify_jwt(&token, pk, JwtAlgorithm::EdDsa, &check)?;
+        // 算法与header不一致
+        assert!(verify_jwt(&token, pk, JwtAlgorithm::Hs256, &check).is_err());
+
+        check.audience = Some("admin".to_string());
+        let err = verify_jwt(&token, pk, JwtAlgorithm::EdDsa, &check).unwrap_err();
+        assert!(err.to_string().starts_with("aud不匹配"));
+        check.audience = None;
+        check.now = NOW + 60;
+        let err = verify_jwt(&token, pk, JwtAlgorithm::EdDsa, &check).unwrap_err();
+        assert!(err.to_string().starts_with("token已于"));
+        check.leeway = 10;
+        verify_jwt(&token, pk, JwtAlgorithm::EdDsa, &check)?;
+
+        // 篡改claims后签名失效
+        let (header, rest) = token.split_once('.').unwrap();
+        let forged_claims = json!({ "sub": "admin", "exp": NOW + 60 });
+        let forged = format!(
+            "{}.{}.{}",
+            header,
+            encode_base64(&serde_json::to_vec(&forged_claims)?, JWT_BASE64)?,
+            rest.split_once('.').unwrap().1
+        );
+        let err = verify_jwt(&forged, pk, JwtAlgorithm::EdDsa, &validation()).unwrap_err();
+        assert!(err.to_string().starts_with("签名验证失败"));
+        Ok(())
+    }
+
+    // 测试exp、nbf及误差很大时不会溢出
+    #[test]
+    fn test_verify_jwt_time_overflow() -> Result<()> {
+        let key = "fixtures/process_text/key.txt";
+        let check = JwtValidation {
+            leeway: i64::MAX,
+            ..validation()
+        };
+        let claims = json!({ "exp": i64::MAX, "nbf": i64::MAX });
+        let token = sign_jwt(&claims, key, JwtAlgorithm::Hs256)?;
+        let err = verify_jwt(&token, key, JwtAlgorithm::Hs256, &validation()).unwrap_err();
+        assert!(err.to_string().contains("之前不可用"));
+        verify_jwt(&token, key, JwtAlgorithm::Hs256, &check)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_jwt() -> Result<()> {
+        let token = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.\
+                     eyJzdWIiOiIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiaWF0IjoxNTE2MjM5MDIyfQ.\
+                     SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c";
+        let jwt = decode_jwt(token)?;
+        assert_eq!(jwt.header["alg"], "HS256");
+        assert_eq!(jwt.claims["name"], "John Doe");
+        let output = format_jwt(&jwt, NOW)?;
+        assert!(output.contains("  iat: 1516239022 ("));
+        assert!(output.ends_with("签发时间)\n"));
+
+        assert!(decode_jwt("abc.def").is_err());
+        Ok(())
+    }
+}
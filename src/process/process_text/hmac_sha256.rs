@@ -0,0 +1,53 @@
+use super::{KeyLoader, TextSign, TextVerify};
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+use std::{fs, io::Read, path::Path};
+
+pub struct HmacSha256 {
+    pub key: Vec<u8>,
+}
+
+impl HmacSha256 {
+    pub fn new(key: Vec<u8>) -> Self {
+        Self { key }
+    }
+
+    fn mac(&self, reader: &mut dyn Read) -> Result<Hmac<Sha256>> {
+        let mut vec = Vec::new();
+        reader.read_to_end(&mut vec)?;
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).map_err(|e| anyhow!("无效的密钥: {}", e))?;
+        mac.update(&vec);
+        Ok(mac)
+    }
+}
+
+impl TextSign for HmacSha256 {
+    fn sign(&self, reader: &mut dyn Read) -> Result<Vec<u8>> {
+        Ok(self.mac(reader)?.finalize().into_bytes().to_vec())
+    }
+}
+
+impl TextVerify for HmacSha256 {
+    fn verify(&self, reader: &mut dyn Read, signature: &[u8]) -> Result<bool> {
+        Ok(self.mac(reader)?.verify_slice(signature).is_ok())
+    }
+}
+
+impl KeyLoader for HmacSha256 {
+    // 去掉编辑器在文件末尾添加的一个换行（\n或\r\n），其余内容按原始字节作为密钥
+    fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let mut key = fs::read(path)?;
+        if key.ends_with(b"\n") {
+            key.pop();
+            if key.ends_with(b"\r") {
+                key.pop();
+            }
+        }
+        if key.is_empty() {
+            return Err(anyhow!("密钥文件为空"));
+        }
+        Ok(Self::new(key))
+    }
+}
@@ -1,13 +1,17 @@
 mod blake3;
 mod ed25519_signer;
 mod ed25519_verifier;
+mod hmac_sha256;
 
 use crate::{utils::get_reader_from_path, TextSignFormatType};
 use anyhow::Result;
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use std::{io::Read, path::Path};
 
-use self::{blake3::Blake3, ed25519_signer::Ed25519Signer, ed25519_verifier::Ed25519Verifier};
+pub use self::{
+    blake3::Blake3, ed25519_signer::Ed25519Signer, ed25519_verifier::Ed25519Verifier,
+    hmac_sha256::HmacSha256,
+};
 
 pub fn sign_text(text: &str, key: &str, format: TextSignFormatType) -> Result<String> {
     let mut reader = get_reader_from_path(text)?;
@@ -87,6 +91,25 @@ mod test {
         Ok(())
     }
 
+    // 测试HMAC密钥文件末尾的一个换行不作为密钥的内容
+    #[test]
+    fn test_hmac_sha256_key_trailing_newline() -> Result<()> {
+        let key = HmacSha256::load("fixtures/process_text/key.txt")?;
+        let content = std::fs::read("fixtures/process_text/key.txt")?;
+        assert_eq!(key.key, content.trim_ascii_end());
+
+        let path = std::env::temp_dir().join(format!("rrcli_hmac_key_{}", std::process::id()));
+        std::fs::write(&path, b"secret\r\n")?;
+        let crlf = HmacSha256::load(&path)?;
+        std::fs::write(&path, b"secret\n\n")?;
+        let double = HmacSha256::load(&path)?;
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(crlf.key, b"secret");
+        assert_eq!(double.key, b"secret\n");
+        Ok(())
+    }
+
     #[test]
     fn test_ed25519_sign_text() -> Result<()> {
         let sk = Ed25519Signer::load("fixtures/process_text/ed25519.sk")?;
@@ -1,8 +1,9 @@
 use anyhow::Result;
 use std::{
     fs::{write, File},
-    io::stdin,
+    io::{self, stdin, stdout, BufWriter, Write},
     path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
 };
 
 pub fn save_str_in_file(save_path: String, content: String) -> Result<()> {
@@ -18,6 +19,15 @@ pub fn verify_file(s: &str) -> Result<String, String> {
     }
 }
 
+// 校验输入文件，包含通配符的glob模式由调用方匹配，已存在的文件按字面路径处理
+pub fn verify_input(s: &str) -> Result<String, String> {
+    if Path::new(s).is_file() || s.contains(['*', '?', '[']) {
+        Ok(s.into())
+    } else {
+        verify_file(s)
+    }
+}
+
 pub fn verify_dir(s: &str) -> Result<PathBuf, String> {
     let path = Path::new(s);
     if path.exists() && path.is_dir() {
@@ -27,6 +37,23 @@ pub fn verify_dir(s: &str) -> Result<PathBuf, String> {
     }
 }
 
+// 解析单字节分隔符，支持 \t 或 tab 表示制表符
+pub fn parse_delimiter(s: &str) -> Result<u8, String> {
+    match s {
+        "\\t" | "tab" => Ok(b'\t'),
+        _ if s.len() == 1 => Ok(s.as_bytes()[0]),
+        _ => Err(format!("分隔符必须为单个ASCII字符: {}", s)),
+    }
+}
+
+// 解析单个ASCII字符参数，如引号、转义字符、注释字符
+pub fn parse_ascii_char(s: &str) -> Result<u8, String> {
+    match s.as_bytes() {
+        [c] if c.is_ascii() => Ok(*c),
+        _ => Err(format!("必须为单个ASCII字符: {}", s)),
+    }
+}
+
 pub fn get_reader_from_path(path: &str) -> Result<Box<dyn std::io::Read>> {
     if path == "-" {
         Ok(Box::new(stdin()))
@@ -35,6 +62,51 @@ pub fn get_reader_from_path(path: &str) -> Result<Box<dyn std::io::Read>> {
     }
 }
 
+pub fn get_writer_from_path(path: &str) -> Result<Box<dyn Write>> {
+    if path == "-" {
+        Ok(Box::new(StdoutWriter(BufWriter::new(stdout().lock()))))
+    } else {
+        Ok(Box::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+// 标准输出的管道是否已被下游关闭
+static STDOUT_CLOSED: AtomicBool = AtomicBool::new(false);
+
+// 记录写出标准输出时遇到的管道关闭错误，json、yaml等序列化库返回的错误中不一定保留io错误类型
+struct StdoutWriter<W: Write>(W);
+
+impl<W: Write> StdoutWriter<W> {
+    fn check<T>(result: io::Result<T>) -> io::Result<T> {
+        if let Err(e) = &result {
+            if e.kind() == io::ErrorKind::BrokenPipe {
+                STDOUT_CLOSED.store(true, Ordering::SeqCst);
+            }
+        }
+        result
+    }
+}
+
+impl<W: Write> Write for StdoutWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Self::check(self.0.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Self::check(self.0.flush())
+    }
+}
+
+// 错误是否由输出管道被关闭引起，如 `rrcli csv ... | head -1`
+pub fn is_broken_pipe(err: &anyhow::Error) -> bool {
+    STDOUT_CLOSED.load(Ordering::SeqCst)
+        || err.chain().any(|cause| {
+            cause
+                .downcast_ref::<io::Error>()
+                .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+        })
+}
+
 pub fn get_string_from_path(path: &str) -> Result<String> {
     let mut buffer = String::new();
     let mut reader = get_reader_from_path(path)?;
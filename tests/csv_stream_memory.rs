@@ -0,0 +1,124 @@
+// 验证流式转换csv时内存占用不随输入大小增长
+// 使用统计分配量的全局分配器，因此单独放在一个测试二进制中
+use anyhow::Result;
+use rrcli::{convert_csv_stream, CsvConvertConfig, CsvFormatType};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    io::{self, Read},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+struct CountingAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+// 按需生成csv内容的读取器，不会一次性把数据放入内存
+struct GeneratedCsv {
+    rows: usize,
+    next_row: usize,
+    buffer: Vec<u8>,
+    pos: usize,
+}
+
+impl GeneratedCsv {
+    fn new(rows: usize) -> Self {
+        Self {
+            rows,
+            next_row: 0,
+            buffer: b"id,name,score,active\n".to_vec(),
+            pos: 0,
+        }
+    }
+}
+
+impl Read for GeneratedCsv {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.buffer.len() {
+            if self.next_row == self.rows {
+                return Ok(0);
+            }
+            self.buffer.clear();
+            self.pos = 0;
+            let i = self.next_row;
+            self.buffer.extend_from_slice(
+                format!("{},player {},{}.5,{}\n", i, i, i % 100, i.is_multiple_of(2)).as_bytes(),
+            );
+            self.next_row += 1;
+        }
+        let n = buf.len().min(self.buffer.len() - self.pos);
+        buf[..n].copy_from_slice(&self.buffer[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+// 统计写出的字节数后丢弃数据
+struct CountingSink(usize);
+
+impl io::Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_convert_csv_stream_memory_bounded() -> Result<()> {
+    const ROWS: usize = 100_000;
+    const MAX_PEAK: usize = 256 * 1024;
+
+    let config = CsvConvertConfig {
+        infer_types: true,
+        ..Default::default()
+    };
+
+    for format in [
+        CsvFormatType::Json,
+        CsvFormatType::Ndjson,
+        CsvFormatType::Yaml,
+        CsvFormatType::Toml,
+        CsvFormatType::Xml,
+        CsvFormatType::Markdown,
+        CsvFormatType::Html,
+        CsvFormatType::MessagePack,
+        CsvFormatType::Cbor,
+    ] {
+        let baseline = CURRENT.load(Ordering::SeqCst);
+        PEAK.store(baseline, Ordering::SeqCst);
+
+        let mut sink = CountingSink(0);
+        let count = convert_csv_stream(GeneratedCsv::new(ROWS), &mut sink, format, &config, None)?;
+        let peak = PEAK.load(Ordering::SeqCst) - baseline;
+
+        assert_eq!(count, ROWS);
+        // 输出远大于允许的内存峰值，说明数据没有被整体缓存
+        assert!(sink.0 > MAX_PEAK * 8, "{} 输出过小: {}", format, sink.0);
+        assert!(peak < MAX_PEAK, "{} 内存峰值过高: {} bytes", format, peak);
+    }
+
+    Ok(())
+}